@@ -0,0 +1,93 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use poll_channel::{channel, Poll, PollEvent};
+
+const MESSAGES: u64 = 10_000;
+
+fn send(c: &mut Criterion) {
+    let mut group = c.benchmark_group("send");
+    group.throughput(Throughput::Elements(MESSAGES));
+
+    group.bench_function("crossbeam", |b| {
+        let (tx, rx) = crossbeam::channel::unbounded();
+        b.iter_batched(
+            || rx.try_iter().for_each(drop),
+            |_| (0..MESSAGES).for_each(|i| tx.send(i).unwrap()),
+            BatchSize::SmallInput,
+        );
+    });
+
+    // what Sender::send does by hand, an id pushed to a signal queue per message
+    group.bench_function("crossbeam + notification", |b| {
+        let (tx, rx) = crossbeam::channel::unbounded();
+        let (signal, ids) = crossbeam::channel::unbounded();
+        b.iter_batched(
+            || {
+                rx.try_iter().for_each(drop);
+                ids.try_iter().for_each(drop);
+            },
+            |_| {
+                (0..MESSAGES).for_each(|i| {
+                    tx.send(i).unwrap();
+                    signal.send(0).unwrap();
+                })
+            },
+            BatchSize::SmallInput,
+        );
+    });
+
+    group.bench_function("poll-channel", |b| {
+        let (tx, rx) = channel();
+        b.iter_batched(
+            || while rx.try_recv().is_ok() {},
+            |_| (0..MESSAGES).for_each(|i| tx.send(i).unwrap()),
+            BatchSize::SmallInput,
+        );
+    });
+
+    group.bench_function("poll-channel registered", |b| {
+        let (tx, rx) = channel();
+        let poller = Poll::new();
+        poller.add(&rx);
+        b.iter_batched(
+            || {
+                while rx.try_recv().is_ok() {}
+                while poller.try_poll() != PollEvent::Timeout {}
+            },
+            |_| (0..MESSAGES).for_each(|i| tx.send(i).unwrap()),
+            BatchSize::SmallInput,
+        );
+    });
+
+    group.finish();
+}
+
+/// A consumer keeping up, each message received right after it's sent
+fn send_recv(c: &mut Criterion) {
+    let mut group = c.benchmark_group("send + recv");
+    group.throughput(Throughput::Elements(MESSAGES));
+
+    group.bench_function("crossbeam", |b| {
+        let (tx, rx) = crossbeam::channel::unbounded();
+        b.iter(|| {
+            (0..MESSAGES).for_each(|i| {
+                tx.send(i).unwrap();
+                rx.try_recv().unwrap();
+            })
+        });
+    });
+
+    group.bench_function("poll-channel", |b| {
+        let (tx, rx) = channel();
+        b.iter(|| {
+            (0..MESSAGES).for_each(|i| {
+                tx.send(i).unwrap();
+                rx.try_recv().unwrap();
+            })
+        });
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, send, send_recv);
+criterion_main!(benches);
@@ -0,0 +1,263 @@
+//! Broadcast channel, every message is received by every subscriber.
+//!
+//! Each subscriber is a [`Receiver`] with its own channel id, registered with
+//! its own poll. The channel keeps the last `capacity` messages, a subscriber
+//! falling further behind skips the oldest ones and gets [`RecvError::Lagged`].
+//!
+//!```rust
+//!use poll_channel::{broadcast, Poll, PollEvent};
+//!
+//!let (tx, rx1) = broadcast(16);
+//!let rx2 = tx.subscribe();
+//!let poller = Poll::new();
+//!poller.append(&[&rx1, &rx2]);
+//!
+//!tx.send("hello").unwrap();
+//!assert!(poller.try_poll() == PollEvent::Ready(rx1.id()));
+//!assert!(poller.try_poll() == PollEvent::Ready(rx2.id()));
+//!assert!(rx1.recv().unwrap() == "hello" && rx2.recv().unwrap() == "hello");
+//!```
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::{Arc, Condvar, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use crate::{wait_until, ChannelId, Pollable, SendError, Shared, Waker};
+
+/// Create a broadcast channel keeping the last `capacity` messages.
+///
+/// # Panics
+///
+/// If `capacity` is 0.
+pub fn channel<T: Clone>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    assert!(capacity > 0, "broadcast capacity must be positive");
+    let ring = Arc::new(Ring {
+        state: Mutex::new(State {
+            buffer: VecDeque::with_capacity(capacity),
+            head: 0,
+            capacity,
+            senders: 1,
+            subscribers: Vec::new(),
+        }),
+        changed: Condvar::new(),
+    });
+    let sender = Sender { ring };
+    let receiver = sender.subscribe();
+    (sender, receiver)
+}
+
+/// Error of [`Receiver::recv`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecvError {
+    /// this many messages were skipped, the next receive gets the oldest kept one
+    Lagged(u64),
+    /// all senders were dropped and the messages received
+    Disconnected,
+}
+
+/// Error of [`Receiver::try_recv`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryRecvError {
+    Empty,
+    Lagged(u64),
+    Disconnected,
+}
+
+/// Error of [`Receiver::recv_timeout`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecvTimeoutError {
+    Timeout,
+    Lagged(u64),
+    Disconnected,
+}
+
+impl fmt::Display for RecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecvError::Lagged(n) => write!(f, "receiver lagged by {n} messages"),
+            RecvError::Disconnected => "receiving on a closed channel".fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for RecvError {}
+
+struct State<T> {
+    buffer: VecDeque<T>,
+    /// sequence of the oldest message in the buffer
+    head: u64,
+    capacity: usize,
+    senders: usize,
+    subscribers: Vec<Arc<Shared>>,
+}
+
+impl<T> State<T> {
+    /// sequence of the next message sent
+    fn tail(&self) -> u64 {
+        self.head + self.buffer.len() as u64
+    }
+}
+
+struct Ring<T> {
+    state: Mutex<State<T>>,
+    changed: Condvar,
+}
+
+pub struct Sender<T> {
+    ring: Arc<Ring<T>>,
+}
+
+pub struct Receiver<T> {
+    ring: Arc<Ring<T>>,
+    shared: Arc<Shared>,
+    /// sequence of the next message to receive
+    next: Mutex<u64>,
+}
+
+impl<T: Clone> Sender<T> {
+    /// Send to every subscriber, return the number of subscribers.
+    ///
+    /// The oldest message is dropped if the channel is full, fails if there is no subscriber.
+    pub fn send(&self, data: T) -> Result<usize, SendError<T>> {
+        let mut state = self.ring.state.lock().unwrap();
+        if state.subscribers.is_empty() {
+            return Err(SendError(data));
+        }
+        if state.buffer.len() == state.capacity {
+            state.buffer.pop_front();
+            state.head += 1;
+        }
+        state.buffer.push_back(data);
+        for shared in &state.subscribers {
+            shared.sent();
+        }
+        self.ring.changed.notify_all();
+        Ok(state.subscribers.len())
+    }
+
+    /// A new subscriber, receiving the messages sent from now on.
+    pub fn subscribe(&self) -> Receiver<T> {
+        let mut state = self.ring.state.lock().unwrap();
+        let shared = Shared::new();
+        state.subscribers.push(shared.clone());
+        Receiver {
+            ring: self.ring.clone(),
+            shared,
+            next: Mutex::new(state.tail()),
+        }
+    }
+
+    /// Number of subscribers
+    pub fn receiver_count(&self) -> usize {
+        self.ring.state.lock().unwrap().subscribers.len()
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.ring.state.lock().unwrap().senders += 1;
+        Self {
+            ring: self.ring.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut state = self.ring.state.lock().unwrap();
+        state.senders -= 1;
+        if state.senders == 0 {
+            for shared in &state.subscribers {
+                shared.disconnect();
+            }
+            self.ring.changed.notify_all();
+        }
+    }
+}
+
+impl<T: Clone> Receiver<T> {
+    /// channel id of this subscriber
+    pub fn id(&self) -> ChannelId {
+        self.shared.id()
+    }
+
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let state = self.ring.state.lock().unwrap();
+        self.take(&state)
+    }
+
+    pub fn recv(&self) -> Result<T, RecvError> {
+        let mut state = self.ring.state.lock().unwrap();
+        loop {
+            match self.take(&state) {
+                Ok(data) => return Ok(data),
+                Err(TryRecvError::Lagged(n)) => return Err(RecvError::Lagged(n)),
+                Err(TryRecvError::Disconnected) => return Err(RecvError::Disconnected),
+                Err(TryRecvError::Empty) => state = self.ring.changed.wait(state).unwrap(),
+            }
+        }
+    }
+
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        let deadline = Instant::now().checked_add(timeout);
+        let mut state = self.ring.state.lock().unwrap();
+        loop {
+            match self.take(&state) {
+                Ok(data) => return Ok(data),
+                Err(TryRecvError::Lagged(n)) => return Err(RecvTimeoutError::Lagged(n)),
+                Err(TryRecvError::Disconnected) => return Err(RecvTimeoutError::Disconnected),
+                Err(TryRecvError::Empty) => {}
+            }
+            state =
+                wait_until(&self.ring.changed, state, deadline).ok_or(RecvTimeoutError::Timeout)?;
+        }
+    }
+
+    /// Number of messages waiting for this subscriber, skipped ones included
+    pub fn len(&self) -> usize {
+        let state = self.ring.state.lock().unwrap();
+        (state.tail() - *self.next.lock().unwrap()) as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn take(&self, state: &MutexGuard<State<T>>) -> Result<T, TryRecvError> {
+        let mut next = self.next.lock().unwrap();
+        if *next < state.head {
+            let lagged = state.head - *next;
+            *next = state.head;
+            self.shared.skipped(lagged as usize);
+            return Err(TryRecvError::Lagged(lagged));
+        }
+        match state.buffer.get((*next - state.head) as usize) {
+            Some(data) => {
+                *next += 1;
+                self.shared.received();
+                Ok(data.clone())
+            }
+            None if state.senders == 0 => Err(TryRecvError::Disconnected),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let mut state = self.ring.state.lock().unwrap();
+        state.subscribers.retain(|s| !Arc::ptr_eq(s, &self.shared));
+    }
+}
+
+impl<T> Pollable for Receiver<T> {
+    fn id(&self) -> ChannelId {
+        self.shared.id()
+    }
+
+    fn register(&self, waker: Waker) {
+        self.shared.register(0, waker);
+    }
+}
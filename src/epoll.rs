@@ -0,0 +1,180 @@
+use std::{
+    io,
+    ops::BitOr,
+    os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd},
+    time::Instant,
+};
+
+/// Readiness of a file descriptor registered with [`Poll::add_fd`](crate::Poll::add_fd)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Interest(u8);
+
+impl Interest {
+    pub const READABLE: Interest = Interest(1);
+    pub const WRITABLE: Interest = Interest(2);
+
+    pub fn is_readable(&self) -> bool {
+        self.0 & Self::READABLE.0 != 0
+    }
+
+    pub fn is_writable(&self) -> bool {
+        self.0 & Self::WRITABLE.0 != 0
+    }
+
+    fn to_epoll(self) -> u32 {
+        let mut events = 0;
+        if self.is_readable() {
+            events |= libc::EPOLLIN | libc::EPOLLRDHUP;
+        }
+        if self.is_writable() {
+            events |= libc::EPOLLOUT;
+        }
+        events as u32
+    }
+
+    /// Hang up and errors are reported as the registered interest, the next
+    /// read or write returns them.
+    fn from_epoll(events: u32, interest: Interest) -> Interest {
+        let events = events as i32;
+        let closed = events & (libc::EPOLLHUP | libc::EPOLLERR) != 0;
+        let mut ready = 0;
+        if events & (libc::EPOLLIN | libc::EPOLLRDHUP) != 0 || closed {
+            ready |= Self::READABLE.0;
+        }
+        if events & libc::EPOLLOUT != 0 || closed {
+            ready |= Self::WRITABLE.0;
+        }
+        Interest(ready & interest.0)
+    }
+}
+
+impl BitOr for Interest {
+    type Output = Interest;
+
+    fn bitor(self, other: Interest) -> Interest {
+        Interest(self.0 | other.0)
+    }
+}
+
+/// epoll instance of a poll, woken by an eventfd written on channel notification
+pub(crate) struct Epoll {
+    epoll: OwnedFd,
+    wake: OwnedFd,
+    /// interest and token of each descriptor
+    interests: std::sync::Mutex<std::collections::HashMap<RawFd, (Interest, usize)>>,
+}
+
+const WAKE: u64 = u64::MAX;
+
+fn check(result: libc::c_int) -> io::Result<libc::c_int> {
+    if result < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(result)
+    }
+}
+
+impl Epoll {
+    pub(crate) fn new() -> io::Result<Self> {
+        // SAFETY: plain syscalls, the returned descriptors are owned from here
+        let epoll =
+            unsafe { OwnedFd::from_raw_fd(check(libc::epoll_create1(libc::EPOLL_CLOEXEC))?) };
+        let flags = libc::EFD_CLOEXEC | libc::EFD_NONBLOCK;
+        let wake = unsafe { OwnedFd::from_raw_fd(check(libc::eventfd(0, flags))?) };
+        let instance = Self {
+            epoll,
+            wake,
+            interests: Default::default(),
+        };
+        let fd = instance.wake.as_raw_fd();
+        instance.ctl(libc::EPOLL_CTL_ADD, fd, libc::EPOLLIN as u32, WAKE)?;
+        Ok(instance)
+    }
+
+    fn ctl(&self, op: libc::c_int, fd: RawFd, events: u32, token: u64) -> io::Result<()> {
+        let mut event = libc::epoll_event { events, u64: token };
+        // SAFETY: event outlives the call
+        check(unsafe { libc::epoll_ctl(self.epoll.as_raw_fd(), op, fd, &mut event) })?;
+        Ok(())
+    }
+
+    /// Add or modify a descriptor
+    pub(crate) fn add(&self, fd: RawFd, interest: Interest, token: usize) -> io::Result<()> {
+        let mut interests = self.interests.lock().unwrap();
+        let op = match interests.contains_key(&fd) {
+            true => libc::EPOLL_CTL_MOD,
+            false => libc::EPOLL_CTL_ADD,
+        };
+        self.ctl(op, fd, interest.to_epoll(), fd as u64)?;
+        interests.insert(fd, (interest, token));
+        Ok(())
+    }
+
+    pub(crate) fn remove(&self, fd: RawFd) -> io::Result<()> {
+        let mut interests = self.interests.lock().unwrap();
+        if interests.remove(&fd).is_some() {
+            self.ctl(libc::EPOLL_CTL_DEL, fd, 0, 0)?;
+        }
+        Ok(())
+    }
+
+    /// Block until a descriptor is ready or the epoll is woken, nothing is
+    /// consumed, the next `wait` reports it.
+    #[cfg(feature = "async")]
+    pub(crate) fn ready(&self) {
+        let mut event = libc::epoll_event { events: 0, u64: 0 };
+        // SAFETY: room for exactly one event, level triggered ones are kept
+        unsafe { libc::epoll_wait(self.epoll.as_raw_fd(), &mut event, 1, -1) };
+    }
+
+    /// Wake a waiting thread, called on channel notification
+    pub(crate) fn wake(&self) {
+        let one = 1u64;
+        // SAFETY: writes 8 bytes from one
+        unsafe { libc::write(self.wake.as_raw_fd(), &one as *const u64 as *const _, 8) };
+    }
+
+    /// Wait for a ready descriptor and its token until the deadline, None if woken or timed out.
+    pub(crate) fn wait(
+        &self,
+        deadline: Option<Instant>,
+    ) -> io::Result<Option<(RawFd, Interest, usize)>> {
+        let timeout = match deadline {
+            // round up, don't spin on a sub millisecond remainder
+            Some(deadline) => {
+                let left = deadline.saturating_duration_since(Instant::now());
+                let ms = left.as_nanos().div_ceil(1_000_000);
+                ms.min(libc::c_int::MAX as u128) as libc::c_int
+            }
+            None => -1,
+        };
+        let mut event = libc::epoll_event { events: 0, u64: 0 };
+        // SAFETY: room for exactly one event
+        let n = unsafe { libc::epoll_wait(self.epoll.as_raw_fd(), &mut event, 1, timeout) };
+        if n < 0 {
+            let error = io::Error::last_os_error();
+            return match error.kind() {
+                io::ErrorKind::Interrupted => Ok(None),
+                _ => Err(error),
+            };
+        }
+        if n == 0 {
+            return Ok(None);
+        }
+        let token = event.u64;
+        if token == WAKE {
+            let mut count = 0u64;
+            // SAFETY: reads 8 bytes into count, resets the eventfd
+            unsafe { libc::read(self.wake.as_raw_fd(), &mut count as *mut u64 as *mut _, 8) };
+            return Ok(None);
+        }
+        let fd = token as RawFd;
+        let (interest, token) = match self.interests.lock().unwrap().get(&fd) {
+            Some(entry) => *entry,
+            // removed meanwhile
+            None => return Ok(None),
+        };
+        let ready = Interest::from_epoll(event.events, interest);
+        Ok(Some((fd, ready, token)))
+    }
+}
@@ -0,0 +1,193 @@
+#[cfg(target_os = "linux")]
+use std::sync::{Arc, Condvar};
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    task::{Context, Poll as TaskPoll, Waker},
+};
+
+#[cfg(target_os = "linux")]
+use crate::epoll::Epoll;
+use crate::{Poll, PollEvent, Receiver, RecvError, TryRecvError};
+
+/// Tasks waiting on a channel or a poll
+#[derive(Default)]
+pub(crate) struct Wakers {
+    waiting: AtomicBool,
+    wakers: Mutex<Vec<Waker>>,
+}
+
+impl Wakers {
+    /// Register before checking the source again, or a wake may be missed.
+    pub(crate) fn register(&self, waker: &Waker) {
+        let mut wakers = self.wakers.lock().unwrap();
+        if !wakers.iter().any(|w| w.will_wake(waker)) {
+            wakers.push(waker.clone());
+        }
+        drop(wakers);
+        self.waiting.store(true, Ordering::SeqCst);
+    }
+
+    /// Wake every registered task, a single atomic when there is none.
+    pub(crate) fn wake(&self) {
+        if self.waiting.swap(false, Ordering::SeqCst) {
+            let wakers = std::mem::take(&mut *self.wakers.lock().unwrap());
+            for waker in wakers {
+                waker.wake();
+            }
+        }
+    }
+}
+
+/// Future of [`Receiver::recv_async`]
+pub struct RecvFuture<'a, T> {
+    receiver: &'a Receiver<T>,
+}
+
+impl<T> Receiver<T> {
+    /// Receive a message asynchronously, `Err(RecvError)` once disconnected and drained.
+    pub fn recv_async(&self) -> RecvFuture<'_, T> {
+        RecvFuture { receiver: self }
+    }
+
+    fn poll_recv(&self, cx: &mut Context<'_>) -> TaskPoll<Result<T, RecvError>> {
+        let result = match self.try_recv() {
+            Err(TryRecvError::Empty) => {
+                self.shared.wakers.register(cx.waker());
+                self.try_recv()
+            }
+            result => result,
+        };
+        match result {
+            Ok(data) => TaskPoll::Ready(Ok(data)),
+            Err(TryRecvError::Disconnected) => TaskPoll::Ready(Err(RecvError)),
+            Err(TryRecvError::Empty) => TaskPoll::Pending,
+        }
+    }
+}
+
+impl<T> Future for RecvFuture<'_, T> {
+    type Output = Result<T, RecvError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> TaskPoll<Self::Output> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+impl<T> futures_core::Stream for Receiver<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> TaskPoll<Option<T>> {
+        self.poll_recv(cx).map(Result::ok)
+    }
+}
+
+/// Future of [`Poll::next_event`]
+pub struct NextEvent<'a> {
+    poll: &'a Poll,
+}
+
+impl Poll {
+    /// Wait for the next event asynchronously, never returns [`PollEvent::Timeout`].
+    ///
+    /// File descriptors added by `add_fd` are watched by a thread of the poll,
+    /// started by the first task waiting for them.
+    pub fn next_event(&self) -> NextEvent<'_> {
+        NextEvent { poll: self }
+    }
+}
+
+impl Future for NextEvent<'_> {
+    type Output = PollEvent;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> TaskPoll<PollEvent> {
+        let mut event = self.poll.try_poll();
+        if event == PollEvent::Timeout {
+            self.poll.wakers().register(cx.waker());
+            event = self.poll.try_poll();
+        }
+        #[cfg(target_os = "linux")]
+        if event == PollEvent::Timeout {
+            let signal = self.poll.signal.lock().unwrap();
+            let signal = signal.as_ref().unwrap();
+            if let Some(epoll) = &signal.epoll {
+                signal.watcher.arm(epoll, &signal.wakers);
+            }
+        }
+        match event {
+            PollEvent::Timeout => TaskPoll::Pending,
+            event => TaskPoll::Ready(event),
+        }
+    }
+}
+
+/// Wakes the tasks of a poll once one of its descriptors is ready, epoll has
+/// no waker of its own.
+///
+/// Armed by a pending [`NextEvent`], the thread wakes the tasks once, then waits
+/// to be armed again, a descriptor left ready doesn't spin it.
+#[cfg(target_os = "linux")]
+#[derive(Default)]
+pub(crate) struct Watcher {
+    state: Mutex<Watch>,
+    changed: Condvar,
+}
+
+#[cfg(target_os = "linux")]
+#[derive(Default)]
+struct Watch {
+    armed: bool,
+    started: bool,
+    stopped: bool,
+}
+
+#[cfg(target_os = "linux")]
+impl Watcher {
+    /// Watch the descriptors until the next wake, the thread starts on first use.
+    pub(crate) fn arm(self: &Arc<Self>, epoll: &Arc<Epoll>, wakers: &Arc<Wakers>) {
+        let mut state = self.state.lock().unwrap();
+        if state.stopped {
+            return;
+        }
+        state.armed = true;
+        if !state.started {
+            let (watcher, epoll, wakers) = (self.clone(), epoll.clone(), wakers.clone());
+            let spawned = std::thread::Builder::new()
+                .name("poll-channel-epoll".into())
+                .spawn(move || watcher.run(&epoll, &wakers));
+            // without a thread the descriptors are seen on the next channel event
+            state.started = spawned.is_ok();
+        }
+        drop(state);
+        self.changed.notify_one();
+    }
+
+    /// End the thread, the poll is dropped
+    pub(crate) fn stop(&self, epoll: Option<&Epoll>) {
+        self.state.lock().unwrap().stopped = true;
+        self.changed.notify_one();
+        if let Some(epoll) = epoll {
+            epoll.wake();
+        }
+    }
+
+    fn run(&self, epoll: &Epoll, wakers: &Wakers) {
+        loop {
+            let mut state = self.state.lock().unwrap();
+            while !state.armed && !state.stopped {
+                state = self.changed.wait(state).unwrap();
+            }
+            if state.stopped {
+                return;
+            }
+            drop(state);
+            epoll.ready();
+            self.state.lock().unwrap().armed = false;
+            wakers.wake();
+        }
+    }
+}
@@ -4,190 +4,836 @@
 //!
 //!example
 //!```rust
-//!use poll_channel::{channel, Poll};
+//!use poll_channel::{channel, Poll, PollEvent};
 //!
-//!#[test]
-//!fn poll_test() -> Result<(), crossbeam::channel::RecvError> {
+//!fn main() -> Result<(), crossbeam::channel::RecvError> {
 //!    let (tx1, rx1) = channel();
 //!    let (tx2, rx2) = channel();
 //!
 //!    let poller = Poll::new();
-//!    poller.append([&rx1, &rx2]);
+//!    poller.append(&[&rx1, &rx2]);
 //!
 //!    let _ = tx1.send(100);
 //!    let _ = tx2.send(200);
-//!    let mut i = 0;
 //!
-//!    while i < 3 {
-//!        let id = poller.poll(0.01);
-//!        if id == rx1.id() {
-//!            let n1 = rx1.recv()?;
-//!            assert!(n1 == 100);
-//!            i += 1;
-//!        } else if id == rx2.id() {
-//!            let n2 = rx2.recv()?;
-//!            assert!(n2 == 200);
-//!            i += 1;
-//!        } else if id == -1 {
-//!            // timeout
-//!            i += 1;
-//!            break;
+//!    loop {
+//!        match poller.poll_event(0.01) {
+//!            PollEvent::Ready(id) if id == rx1.id() => {
+//!                let n1 = rx1.recv()?;
+//!                assert!(n1 == 100);
+//!            }
+//!            PollEvent::Ready(id) if id == rx2.id() => {
+//!                let n2 = rx2.recv()?;
+//!                assert!(n2 == 200);
+//!            }
+//!            PollEvent::Timeout => break,
+//!            _ => {}
 //!        }
 //!    }
 //!
 //!    Ok(())
 //!}
 //!```
+//!
+//!`Poll::poll` still returns the raw channel id with `-1` for timeout, for compatibility, and `-2 - fd` for a ready file descriptor.
+//!
+//!To dispatch without matching ids, register with a token, `Poll::add_with_token(&rx, token)`, `add_sender_with_token` or `add_fd`, and `Poll::poll_token` returns that token, e.g. an index in a table of handlers, or None for a source added without one.
+//!
+//!Anything can be polled by implementing `Pollable`: `Poll::add` hands the source a `Waker`, which it calls `notify()` on from any thread when it has something to read.
+//!
+//!`Sender::send` takes no lock while the receiver's polls already have an event queued, nor do send and receive on a channel in no poll, `cargo bench` compares them with raw crossbeam.
+//!
+//!## Cargo features
+//!
+//!- `async`: `Receiver::recv_async`, `Stream` for `Receiver` and `Poll::next_event`, so the same channels can be awaited from async tasks.
+#[cfg(target_os = "linux")]
+use std::os::fd::RawFd;
 use std::{
-    sync::{Arc, Mutex},
-    time::Duration,
+    collections::{BTreeSet, HashMap},
+    mem::ManuallyDrop,
+    sync::{
+        atomic::{AtomicBool, AtomicIsize, AtomicU64, AtomicUsize, Ordering},
+        Arc, Condvar, Mutex, MutexGuard,
+    },
+    time::{Duration, Instant},
 };
 
+pub mod broadcast;
+#[cfg(target_os = "linux")]
+mod epoll;
+#[cfg(feature = "async")]
+mod future;
+mod macros;
+pub mod oneshot;
+mod selector;
+#[cfg(unix)]
+mod signal;
+mod timer;
+pub mod watch;
+
+pub use broadcast::channel as broadcast;
+#[cfg(target_os = "linux")]
+pub use epoll::Interest;
+#[cfg(feature = "async")]
+pub use future::{NextEvent, RecvFuture};
+#[doc(hidden)]
+pub use macros::select_wait as __select_wait;
+pub use oneshot::channel as oneshot;
+pub use selector::Selector;
+#[cfg(unix)]
+pub use signal::{signals, SIGHUP, SIGINT, SIGQUIT, SIGTERM, SIGUSR1, SIGUSR2};
+pub use timer::{after, at, tick};
+
 pub use crossbeam::channel::RecvError;
 pub use crossbeam::channel::RecvTimeoutError;
 pub use crossbeam::channel::SendError;
+pub use crossbeam::channel::SendTimeoutError;
 pub use crossbeam::channel::TryRecvError;
+pub use crossbeam::channel::TrySendError;
+
+/// Process wide unique channel id
+///
+/// Ids are never reused, and stay below `i64::MAX` so they never collide with
+/// the `-1` of [`Poll::poll`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ChannelId(u64);
 
-pub struct Signal {
-    tx: crossbeam::channel::Sender<i32>,
-    rx: crossbeam::channel::Receiver<i32>,
+impl ChannelId {
+    /// Allocate a new id, for a custom [`Pollable`] source.
+    ///
+    /// Lock free, panics once `i64::MAX` ids were allocated.
+    pub fn unique() -> Self {
+        let id = UID
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |id| {
+                (id < i64::MAX as u64).then_some(id + 1)
+            })
+            .expect("channel ids exhausted");
+        ChannelId(id)
+    }
+
+    pub fn as_u64(&self) -> u64 {
+        self.0
+    }
+
+    /// raw id, as returned by [`Poll::poll`]
+    pub fn as_i64(&self) -> i64 {
+        self.0 as i64
+    }
+}
+
+impl std::fmt::Display for ChannelId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl From<ChannelId> for u64 {
+    fn from(id: ChannelId) -> Self {
+        id.0
+    }
+}
+
+impl From<ChannelId> for i64 {
+    fn from(id: ChannelId) -> Self {
+        id.as_i64()
+    }
+}
+
+// compare with the raw id returned by Poll::poll
+impl PartialEq<i64> for ChannelId {
+    fn eq(&self, other: &i64) -> bool {
+        self.as_i64() == *other
+    }
+}
+
+impl PartialEq<ChannelId> for i64 {
+    fn eq(&self, other: &ChannelId) -> bool {
+        *self == other.as_i64()
+    }
+}
+
+/// What happened on a polled channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum PollEvent {
+    /// a message was sent to the channel
+    Ready(ChannelId),
+    /// all senders of the channel were dropped
+    Disconnected(ChannelId),
+    /// a [`oneshot()`] sender was dropped without sending
+    Cancelled(ChannelId),
+    /// a message was received from a channel added with [`Poll::add_sender`],
+    /// or its receivers were dropped
+    Writable(ChannelId),
+    /// nothing happened before the timeout
+    Timeout,
+    /// a file descriptor registered with [`Poll::add_fd`] is ready
+    #[cfg(target_os = "linux")]
+    Io(RawFd, Interest),
+}
+
+impl PollEvent {
+    /// channel id of the event, None for timeout
+    pub fn id(&self) -> Option<ChannelId> {
+        match self {
+            PollEvent::Ready(id)
+            | PollEvent::Disconnected(id)
+            | PollEvent::Cancelled(id)
+            | PollEvent::Writable(id) => Some(*id),
+            _ => None,
+        }
+    }
+}
+
+struct Signal {
+    tx: crossbeam::channel::Sender<Notice>,
+    rx: crossbeam::channel::Receiver<Notice>,
+    #[cfg(feature = "async")]
+    wakers: Arc<future::Wakers>,
+    /// created by the first Poll::add_fd
+    #[cfg(target_os = "linux")]
+    epoll: Option<Arc<epoll::Epoll>>,
+    /// wakes the tasks on a ready descriptor
+    #[cfg(all(target_os = "linux", feature = "async"))]
+    watcher: Arc<future::Watcher>,
 }
 
 impl Signal {
     fn new() -> Self {
         let (tx, rx) = crossbeam::channel::unbounded();
-        Self { tx, rx }
+        Self {
+            tx,
+            rx,
+            #[cfg(feature = "async")]
+            wakers: Default::default(),
+            #[cfg(target_os = "linux")]
+            epoll: None,
+            #[cfg(all(target_os = "linux", feature = "async"))]
+            watcher: Default::default(),
+        }
     }
 }
 
-pub struct Sender<T> {
-    init: Mutex<bool>,
-    producer: Mutex<Option<SignalSender>>,
-    signal: ArcMutex2<OptionSignal>,
-    tx: crossbeam::channel::Sender<T>,
-    id: i32,
+/// Queued event, with the channel state to drop stale Ready events
+struct Notice {
+    event: PollEvent,
+    /// pending flag of the registration, for coalesced events
+    pending: Option<Arc<AtomicBool>>,
+    /// channel of a Ready event
+    shared: Option<Arc<Shared>>,
+}
+
+impl Notice {
+    fn new(event: PollEvent) -> Self {
+        Self {
+            event,
+            pending: None,
+            shared: None,
+        }
+    }
+}
+
+type OptionSignal = Option<Signal>;
+type ArcMutex<T> = Arc<Mutex<T>>;
+static UID: AtomicU64 = AtomicU64::new(0);
+
+/// State shared by the senders and the receiver of a channel.
+///
+/// At most one Ready event per channel is queued in each poll, it's armed by a
+/// send and re-armed by a receive which leaves messages behind.
+///
+/// Each receiver clone is a consumer, a message wakes the polls of a single
+/// idle consumer in turn, unless every consumer is already woken.
+pub(crate) struct Shared {
+    /// of every poll a receiver is registered with, by consumer
+    polls: Mutex<Vec<(usize, Waker)>>,
+    /// consumer ids allocated, one per receiver clone
+    consumers: AtomicUsize,
+    /// round robin among idle consumers
+    turn: AtomicUsize,
+    /// every poll has a Ready event queued, sends have nothing to arm.
+    /// Only changed with `polls` locked, so the send path stays lock free
+    saturated: AtomicBool,
+    /// `polls` isn't empty, only changed with it locked, so a receive draining
+    /// the channel stays lock free without them and leaves it saturated
+    has_polls: AtomicBool,
+    /// of every poll a sender is registered with
+    writers: Mutex<Vec<Waker>>,
+    /// `writers` isn't empty, only changed with it locked, so the receive
+    /// path stays lock free without them
+    has_writers: AtomicBool,
+    /// a receive makes room for a sender, not on a rendezvous channel
+    room: bool,
+    receivers: AtomicUsize,
+    id: ChannelId,
+    /// sent but not received messages, briefly negative if a receive wins the race
+    count: AtomicIsize,
+    senders: AtomicUsize,
+    /// closed without sending, see [`oneshot()`]
+    cancelled: AtomicBool,
+    #[cfg(feature = "async")]
+    wakers: future::Wakers,
+}
+
+impl Shared {
+    pub(crate) fn new() -> Arc<Self> {
+        Self::with_room(true)
+    }
+
+    fn with_room(room: bool) -> Arc<Self> {
+        Arc::new(Shared {
+            polls: Mutex::new(Vec::new()),
+            consumers: AtomicUsize::new(1),
+            turn: AtomicUsize::new(0),
+            saturated: AtomicBool::new(false),
+            has_polls: AtomicBool::new(false),
+            writers: Mutex::new(Vec::new()),
+            has_writers: AtomicBool::new(false),
+            room,
+            receivers: AtomicUsize::new(1),
+            id: ChannelId::unique(),
+            count: AtomicIsize::new(0),
+            senders: AtomicUsize::new(1),
+            cancelled: AtomicBool::new(false),
+            #[cfg(feature = "async")]
+            wakers: Default::default(),
+        })
+    }
+
+    pub(crate) fn id(&self) -> ChannelId {
+        self.id
+    }
+
+    /// Polls the receiver is currently registered with.
+    ///
+    /// Looked up on every call, so registering a receiver after its senders
+    /// were used, or removing it from a poll, takes effect immediately.
+    fn polls(&self) -> Vec<Waker> {
+        let mut polls = self.polls.lock().unwrap();
+        self.retain(&mut polls, |(_, waker)| waker.is_active());
+        polls.iter().map(|(_, waker)| waker.clone()).collect()
+    }
+
+    pub(crate) fn sent(self: &Arc<Self>) {
+        self.count.fetch_add(1, Ordering::SeqCst);
+        self.arm();
+        #[cfg(feature = "async")]
+        self.wakers.wake();
+    }
+
+    pub(crate) fn received(self: &Arc<Self>) {
+        self.consumed(1);
+    }
+
+    /// Messages gone without being received
+    pub(crate) fn skipped(self: &Arc<Self>, n: usize) {
+        self.consumed(n as isize);
+    }
+
+    fn consumed(self: &Arc<Self>, n: isize) {
+        if self.room {
+            self.writable();
+        }
+        if self.count.fetch_sub(n, Ordering::SeqCst) > n {
+            self.arm();
+        } else if self.has_polls.load(Ordering::SeqCst) {
+            // drained, the queued events if any are stale now
+            let polls = self.polls.lock().unwrap();
+            for (_, waker) in polls.iter() {
+                waker.pending.store(false, Ordering::SeqCst);
+            }
+            self.saturated.store(false, Ordering::SeqCst);
+            drop(polls);
+            // a send raced with us after seeing pending
+            if self.count.load(Ordering::SeqCst) > 0 {
+                self.arm();
+            }
+        }
+    }
+
+    fn arm(self: &Arc<Self>) {
+        if self.saturated.load(Ordering::SeqCst) {
+            return;
+        }
+        let mut polls = self.polls.lock().unwrap();
+        self.retain(&mut polls, |(_, waker)| waker.is_active());
+        // woken consumers get their other polls completed, one idle consumer is
+        // added while there are more messages than woken consumers
+        let mut woken = BTreeSet::new();
+        for (consumer, waker) in polls.iter() {
+            if waker.pending.load(Ordering::SeqCst) {
+                woken.insert(*consumer);
+            }
+        }
+        let idle: Vec<usize> = polls
+            .iter()
+            .map(|(consumer, _)| *consumer)
+            .filter(|consumer| !woken.contains(consumer))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        if !idle.is_empty() && (woken.len() as isize) < self.count.load(Ordering::SeqCst) {
+            let turn = self.turn.fetch_add(1, Ordering::SeqCst);
+            woken.insert(idle[turn % idle.len()]);
+        }
+        let armed: Vec<Waker> = polls
+            .iter()
+            .filter(|(c, w)| woken.contains(c) && !w.pending.swap(true, Ordering::SeqCst))
+            .map(|(_, waker)| waker.clone())
+            .collect();
+        let saturated = polls.iter().all(|(_, w)| w.pending.load(Ordering::SeqCst));
+        self.saturated.store(saturated, Ordering::SeqCst);
+        // sent unlocked, a woken task may poll right away, an event drained
+        // meanwhile is dropped as stale
+        drop(polls);
+        for waker in armed {
+            let notice = Notice {
+                event: PollEvent::Ready(self.id),
+                pending: Some(waker.pending.clone()),
+                shared: Some(self.clone()),
+            };
+            waker.send(notice);
+        }
+    }
+
+    /// Keep the polls matching, with `polls` locked
+    fn retain(&self, polls: &mut Vec<(usize, Waker)>, keep: impl FnMut(&(usize, Waker)) -> bool) {
+        polls.retain(keep);
+        self.has_polls.store(!polls.is_empty(), Ordering::SeqCst);
+    }
+
+    /// Consume a queued Ready event, false if stale
+    fn take(&self, pending: &AtomicBool) -> bool {
+        let _polls = self.polls.lock().unwrap();
+        let taken = pending.swap(false, Ordering::SeqCst);
+        self.saturated.store(false, Ordering::SeqCst);
+        taken && self.count.load(Ordering::SeqCst) > 0
+    }
+
+    pub(crate) fn is_disconnected(&self) -> bool {
+        self.senders.load(Ordering::SeqCst) == 0
+    }
+
+    /// Tell the poll the last sender is gone
+    pub(crate) fn disconnect(&self) {
+        self.senders.store(0, Ordering::SeqCst);
+        self.closed();
+    }
+
+    /// Tell the poll the sender is gone without sending
+    pub(crate) fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        self.disconnect();
+    }
+
+    fn closed(&self) {
+        let event = self.closed_event();
+        for waker in self.polls() {
+            waker.send(Notice::new(event));
+        }
+        #[cfg(feature = "async")]
+        self.wakers.wake();
+    }
+
+    /// Add a poll and report what is already ready to it
+    pub(crate) fn register(self: &Arc<Self>, consumer: usize, waker: Waker) {
+        let mut polls = self.polls.lock().unwrap();
+        polls.push((consumer, waker.clone()));
+        self.has_polls.store(true, Ordering::SeqCst);
+        self.saturated.store(false, Ordering::SeqCst);
+        drop(polls);
+        if self.count.load(Ordering::SeqCst) > 0 {
+            self.arm();
+        }
+        if self.is_disconnected() {
+            let event = self.closed_event();
+            waker.send(Notice::new(event));
+        }
+    }
+
+    fn closed_event(&self) -> PollEvent {
+        match self.cancelled.load(Ordering::SeqCst) {
+            true => PollEvent::Cancelled(self.id),
+            false => PollEvent::Disconnected(self.id),
+        }
+    }
+
+    /// A new consumer, for a receiver clone
+    pub(crate) fn consumer(&self) -> usize {
+        self.receivers.fetch_add(1, Ordering::SeqCst);
+        self.consumers.fetch_add(1, Ordering::SeqCst)
+    }
+
+    /// Forget the polls of a dropped consumer, its events go to the others
+    pub(crate) fn unregister(self: &Arc<Self>, consumer: usize) {
+        let mut polls = self.polls.lock().unwrap();
+        self.retain(&mut polls, |(c, _)| *c != consumer);
+        self.saturated.store(false, Ordering::SeqCst);
+        drop(polls);
+        if self.count.load(Ordering::SeqCst) > 0 {
+            self.arm();
+        }
+        // sending fails right away now
+        if self.receivers.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.writable();
+        }
+    }
+
+    /// Add a poll waiting for room, `ready` tells if there is some already.
+    ///
+    /// Checked once registered, a receive meanwhile reports the room itself.
+    pub(crate) fn register_writer(&self, waker: Waker, ready: impl FnOnce() -> bool) {
+        let mut writers = self.writers.lock().unwrap();
+        writers.push(waker);
+        self.has_writers.store(true, Ordering::SeqCst);
+        drop(writers);
+        if ready() {
+            self.writable();
+        }
+    }
+
+    fn writable(&self) {
+        if !self.has_writers.load(Ordering::SeqCst) {
+            return;
+        }
+        let mut writers = self.writers.lock().unwrap();
+        writers.retain(Waker::is_active);
+        self.has_writers
+            .store(!writers.is_empty(), Ordering::SeqCst);
+        let armed: Vec<Waker> = writers
+            .iter()
+            .filter(|waker| !waker.pending.swap(true, Ordering::SeqCst))
+            .cloned()
+            .collect();
+        drop(writers);
+        for waker in armed {
+            let notice = Notice {
+                event: PollEvent::Writable(self.id),
+                pending: Some(waker.pending.clone()),
+                shared: None,
+            };
+            waker.send(notice);
+        }
+    }
 }
 
-pub type SignalSender = crossbeam::channel::Sender<i32>;
-pub type OptionSignal = Option<Signal>;
-pub type ArcMutex<T> = Arc<Mutex<T>>;
-pub type ArcMutex2<T> = ArcMutex<ArcMutex<T>>;
-static UID: Mutex<i32> = Mutex::new(0);
+pub struct Sender<T> {
+    shared: Arc<Shared>,
+    // dropped before the poll is told about the disconnection
+    tx: ManuallyDrop<crossbeam::channel::Sender<T>>,
+}
 
+/// Receiving side of a channel.
+///
+/// Receivers can be cloned for a pool of workers, each message goes to one of
+/// them, and a send wakes the poll of one idle clone only.
 pub struct Receiver<T> {
-    signal: ArcMutex2<OptionSignal>,
+    shared: Arc<Shared>,
     rx: crossbeam::channel::Receiver<T>,
-    id: i32,
+    /// this clone, see [`Shared`]
+    consumer: usize,
 }
 
+/// Create an unbounded channel.
 pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
-    let inner = Arc::new(Mutex::new(None));
-    let signal = Arc::new(Mutex::new(inner));
     let (tx, rx) = crossbeam::channel::unbounded();
-    let mut id = UID.lock().unwrap();
-    let next = *id;
-    *id += 1;
+    pair(tx, rx)
+}
+
+/// Create a channel holding at most `cap` messages, `send` blocks while it is full.
+///
+/// A zero capacity channel is a rendezvous channel, see [`rendezvous`].
+pub fn bounded<T>(cap: usize) -> (Sender<T>, Receiver<T>) {
+    let (tx, rx) = crossbeam::channel::bounded(cap);
+    pair(tx, rx)
+}
+
+/// Create a zero capacity channel, `send` blocks until a receiver takes the message.
+///
+/// The poll reports the channel ready once a sender is about to block, it may
+/// not be parked yet, so `try_recv` right after the event can find nothing.
+/// `recv` waits for it instead, the sender is committed to the message.
+pub fn rendezvous<T>() -> (Sender<T>, Receiver<T>) {
+    bounded(0)
+}
+
+fn pair<T>(
+    tx: crossbeam::channel::Sender<T>,
+    rx: crossbeam::channel::Receiver<T>,
+) -> (Sender<T>, Receiver<T>) {
+    let shared = Shared::with_room(tx.capacity() != Some(0));
     let receiver = Receiver {
-        signal,
+        shared: shared.clone(),
         rx,
-        id: next,
+        consumer: 0,
     };
     let sender = Sender {
-        producer: Mutex::new(None),
-        signal: receiver.signal.clone(),
-        tx,
-        id: next,
-        init: Mutex::new(false),
+        shared,
+        tx: ManuallyDrop::new(tx),
     };
     (sender, receiver)
 }
 
 impl<T> Clone for Sender<T> {
     fn clone(&self) -> Self {
+        self.shared.senders.fetch_add(1, Ordering::SeqCst);
         Self {
-            init: Mutex::new(false),
-            producer: Mutex::new(None),
-            signal: self.signal.clone(),
-            tx: self.tx.clone(),
-            id: self.id,
+            shared: self.shared.clone(),
+            tx: ManuallyDrop::new((*self.tx).clone()),
         }
     }
 }
 
 impl<T> Sender<T> {
+    /// Send a message, blocks while a bounded channel is full.
+    /// The poll is notified only after the message was enqueued.
+    ///
+    /// Lock free once the polls have a Ready event queued, or without any poll,
+    /// see `cargo bench`.
     pub fn send(&self, data: T) -> Result<(), SendError<T>> {
-        // avoid mutable, no one races for the mutexes
-        let mut init = self.init.lock().unwrap();
-        let mut producer = self.producer.lock().unwrap();
-        if !*init {
-            *init = true;
-            let inner = self.signal.lock().unwrap();
-            let signal = inner.lock().unwrap();
-            if signal.is_some() {
-                let tx = signal.as_ref().unwrap().tx.clone();
-                *producer = Some(tx);
-            }
+        self.blocking(|| self.tx.send(data))
+    }
+
+    /// Send a message if the channel has room, without blocking.
+    pub fn try_send(&self, data: T) -> Result<(), TrySendError<T>> {
+        self.tx.try_send(data)?;
+        self.shared.sent();
+        Ok(())
+    }
+
+    /// channel capacity, None for unbounded channel
+    pub fn capacity(&self) -> Option<usize> {
+        self.tx.capacity()
+    }
+
+    /// Send a message, waiting up to timeout for room in a bounded channel.
+    pub fn send_timeout(&self, data: T, timeout: Duration) -> Result<(), SendTimeoutError<T>> {
+        self.blocking(|| self.tx.send_timeout(data, timeout))
+    }
+
+    /// Send a message, waiting until the deadline for room in a bounded channel.
+    pub fn send_deadline(&self, data: T, deadline: Instant) -> Result<(), SendTimeoutError<T>> {
+        self.blocking(|| self.tx.send_deadline(data, deadline))
+    }
+
+    /// Notify once sent, or right before blocking on a rendezvous channel, which
+    /// has nothing to report otherwise. Taken back if the send fails.
+    fn blocking<E>(&self, send: impl FnOnce() -> Result<(), E>) -> Result<(), E> {
+        if self.tx.capacity() != Some(0) {
+            send()?;
+            self.shared.sent();
+            return Ok(());
         }
-        let result = self.tx.send(data);
-        if let Some(signal) = &*producer {
-            let _ = signal.send(self.id);
+        self.shared.sent();
+        send().inspect_err(|_| self.shared.skipped(1))
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        // SAFETY: dropped only here, never used afterwards
+        unsafe { ManuallyDrop::drop(&mut self.tx) };
+        // the last sender tells the poll the channel is closed
+        if self.shared.senders.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.shared.disconnect();
         }
-        return result;
+    }
+}
+
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Self {
+        Self {
+            shared: self.shared.clone(),
+            rx: self.rx.clone(),
+            consumer: self.shared.consumer(),
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.shared.unregister(self.consumer);
     }
 }
 
 impl<T> Receiver<T> {
     /// channel id
-    pub fn id(&self) -> i32 {
-        self.id
+    pub fn id(&self) -> ChannelId {
+        self.shared.id
     }
 
     pub fn recv(&self) -> Result<T, RecvError> {
-        self.rx.recv()
+        let data = self.rx.recv()?;
+        self.shared.received();
+        Ok(data)
     }
 
     pub fn recv_timeout(
         &self,
         timeout: Duration,
     ) -> Result<T, crossbeam::channel::RecvTimeoutError> {
-        self.rx.recv_timeout(timeout)
+        let data = self.rx.recv_timeout(timeout)?;
+        self.shared.received();
+        Ok(data)
     }
 
     pub fn try_recv(&self) -> Result<T, TryRecvError> {
-        self.rx.try_recv()
+        let data = self.rx.try_recv()?;
+        self.shared.received();
+        Ok(data)
     }
 
     pub fn len(&self) -> usize {
         self.rx.len()
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.rx.is_empty()
+    }
+
+    /// all senders were dropped, messages may still be waiting
+    pub fn is_disconnected(&self) -> bool {
+        self.shared.is_disconnected()
+    }
+
+    /// channel capacity, None for unbounded channel
+    pub fn capacity(&self) -> Option<usize> {
+        self.rx.capacity()
+    }
+}
+
+/// Handle to wake a poll, given to a source by [`Poll::add`].
+///
+/// Cheap to clone and usable from any thread. It does nothing once the source
+/// is removed from the poll, or the poll is dropped.
+#[derive(Clone)]
+pub struct Waker {
+    signal: ArcMutex<OptionSignal>,
+    id: ChannelId,
+    active: Arc<AtomicBool>,
+    /// a valid Ready event of a channel is queued
+    pending: Arc<AtomicBool>,
+    /// chosen by the caller, see [`Poll::add_with_token`]
+    token: Option<usize>,
 }
 
+impl Waker {
+    /// id of the source
+    pub fn id(&self) -> ChannelId {
+        self.id
+    }
+
+    /// Report the source ready, every call queues a [`PollEvent::Ready`].
+    pub fn notify(&self) {
+        self.send(Notice::new(PollEvent::Ready(self.id)));
+    }
+
+    /// Report the source closed with [`PollEvent::Disconnected`].
+    pub fn disconnect(&self) {
+        self.send(Notice::new(PollEvent::Disconnected(self.id)));
+    }
+
+    /// false once removed from the poll
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::SeqCst)
+    }
+
+    fn send(&self, notice: Notice) {
+        if !self.is_active() {
+            return;
+        }
+        let signal = self.signal.lock().unwrap();
+        let Some(queue) = &*signal else {
+            return;
+        };
+        let _ = queue.tx.send(notice);
+        #[cfg(feature = "async")]
+        let wakers = queue.wakers.clone();
+        #[cfg(target_os = "linux")]
+        let epoll = queue.epoll.clone();
+        // a task may poll right away when woken, don't hold the lock
+        drop(signal);
+        #[cfg(feature = "async")]
+        wakers.wake();
+        #[cfg(target_os = "linux")]
+        if let Some(epoll) = epoll {
+            epoll.wake();
+        }
+    }
+}
+
+/// A source of events for [`Poll`].
+///
+/// Channels of this crate implement it, a custom source keeps the waker it
+/// is given and calls [`Waker::notify`] whenever it has something to read:
+///
+///```rust
+///use poll_channel::{ChannelId, Poll, PollEvent, Pollable, Waker};
+///use std::sync::Mutex;
+///
+///struct Doorbell {
+///    id: ChannelId,
+///    waker: Mutex<Option<Waker>>,
+///}
+///
+///impl Pollable for Doorbell {
+///    fn id(&self) -> ChannelId {
+///        self.id
+///    }
+///
+///    fn register(&self, waker: Waker) {
+///        *self.waker.lock().unwrap() = Some(waker);
+///    }
+///}
+///
+///let bell = Doorbell { id: ChannelId::unique(), waker: Mutex::new(None) };
+///let poller = Poll::new();
+///poller.add(&bell);
+///
+///let waker = bell.waker.lock().unwrap().clone().unwrap();
+///std::thread::spawn(move || waker.notify());
+///assert!(poller.poll_blocking() == PollEvent::Ready(bell.id));
+///```
 pub trait Pollable {
-    /// shared signal channel
-    fn signal(&self) -> ArcMutex2<OptionSignal>;
-    /// channel id
-    fn id(&self) -> i32;
+    /// channel id, see [`ChannelId::unique`]
+    fn id(&self) -> ChannelId;
+    /// Called by [`Poll::add`] with the waker of the poll, notify it right away if
+    /// the source is already ready. A source added to several polls gets a waker
+    /// from each, the inactive ones can be dropped, see [`Waker::is_active`].
+    fn register(&self, waker: Waker);
 }
 
 impl<T> Pollable for Receiver<T> {
-    fn signal(&self) -> ArcMutex2<OptionSignal> {
-        self.signal.clone()
+    fn id(&self) -> ChannelId {
+        self.shared.id
     }
 
-    fn id(&self) -> i32 {
-        self.id
+    fn register(&self, waker: Waker) {
+        self.shared.register(self.consumer, waker);
     }
 }
 
 pub struct Poll {
     signal: ArcMutex<OptionSignal>,
+    receivers: Mutex<HashMap<ChannelId, Waker>>,
+    senders: Mutex<HashMap<ChannelId, Waker>>,
+}
+
+impl Default for Poll {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Poll {
     pub fn new() -> Self {
         let instance = Signal::new();
         let inner = Arc::new(Mutex::new(Some(instance)));
-        Self { signal: inner }
+        Self {
+            signal: inner,
+            receivers: Mutex::new(HashMap::new()),
+            senders: Mutex::new(HashMap::new()),
+        }
     }
 
     /// Append list of receivers
@@ -197,23 +843,334 @@ impl Poll {
         }
     }
 
-    /// Add single receiver
+    /// Add single receiver, it may be registered with other polls as well, each gets the events.
+    ///
+    /// It has no token, see [`Poll::add_with_token`].
     pub fn add<T: Pollable>(&self, receiver: &T) {
-        let outer = receiver.signal();
-        let mut inner = outer.lock().unwrap();
-        *inner = self.signal.clone();
+        receiver.register(self.waker(&self.receivers, receiver.id(), None));
+    }
+
+    /// Add single receiver, [`Poll::poll_token`] reports its events with `token`,
+    /// e.g. an index in the caller's table of handlers.
+    pub fn add_with_token<T: Pollable>(&self, receiver: &T, token: usize) {
+        receiver.register(self.waker(&self.receivers, receiver.id(), Some(token)));
+    }
+
+    /// Remove single receiver, its queued notifications are discarded.
+    pub fn remove<T: Pollable>(&self, receiver: &T) {
+        self.forget(receiver.id());
+    }
+
+    pub(crate) fn forget(&self, id: ChannelId) {
+        let waker = self.receivers.lock().unwrap().remove(&id);
+        if let Some(waker) = waker {
+            waker.active.store(false, Ordering::SeqCst);
+        }
+        self.purge(|event| event.id() == Some(id) && *event != PollEvent::Writable(id));
+    }
+
+    /// Add a sender, [`PollEvent::Writable`] is reported once the channel has room.
+    ///
+    /// Reported right away if there is room already, then every time a message
+    /// is received, at most once until polled. Another sender may fill the room
+    /// first, `try_send` tells. A rendezvous channel never has room.
+    pub fn add_sender<T>(&self, sender: &Sender<T>) {
+        self.writer(sender, None);
+    }
+
+    /// Add a sender, [`Poll::poll_token`] reports its events with `token`.
+    pub fn add_sender_with_token<T>(&self, sender: &Sender<T>, token: usize) {
+        self.writer(sender, Some(token));
+    }
+
+    fn writer<T>(&self, sender: &Sender<T>, token: Option<usize>) {
+        let id = sender.shared.id;
+        let waker = self.waker(&self.senders, id, token);
+        let shared = &sender.shared;
+        shared.register_writer(waker, || {
+            !sender.tx.is_full() || shared.receivers.load(Ordering::SeqCst) == 0
+        });
+    }
+
+    /// Remove a sender, its queued notifications are discarded.
+    pub fn remove_sender<T>(&self, sender: &Sender<T>) {
+        let id = sender.shared.id;
+        let waker = self.senders.lock().unwrap().remove(&id);
+        if let Some(waker) = waker {
+            waker.active.store(false, Ordering::SeqCst);
+        }
+        self.purge(|event| *event == PollEvent::Writable(id));
+    }
+
+    /// Remove all receivers and senders
+    pub fn clear(&self) {
+        for map in [&self.receivers, &self.senders] {
+            let wakers = std::mem::take(&mut *map.lock().unwrap());
+            for waker in wakers.values() {
+                waker.active.store(false, Ordering::SeqCst);
+            }
+        }
+        self.purge(|_| true);
+    }
+
+    /// A new waker of this poll, replacing the one of a source added before
+    fn waker(
+        &self,
+        map: &Mutex<HashMap<ChannelId, Waker>>,
+        id: ChannelId,
+        token: Option<usize>,
+    ) -> Waker {
+        let waker = Waker {
+            signal: self.signal.clone(),
+            id,
+            active: Arc::new(AtomicBool::new(true)),
+            pending: Arc::new(AtomicBool::new(false)),
+            token,
+        };
+        let old = map.lock().unwrap().insert(id, waker.clone());
+        if let Some(old) = old {
+            old.active.store(false, Ordering::SeqCst);
+        }
+        waker
+    }
+
+    /// Poll with decimal seconds timeout, return raw channel id, -1 for timeout.
+    ///
+    /// Kept for compatibility, a disconnected channel is reported by its id as well,
+    /// and a ready file descriptor as `-2 - fd`, prefer [`Poll::poll_event`].
+    pub fn poll(&self, timeout: f32) -> i64 {
+        match self.poll_event(timeout) {
+            #[cfg(target_os = "linux")]
+            PollEvent::Io(fd, _) => -2 - i64::from(fd),
+            event => event.id().map_or(-1, i64::from),
+        }
+    }
+
+    /// Poll with decimal seconds timeout, return the event.
+    ///
+    /// Negative or NaN timeout doesn't wait, an infinite one blocks.
+    pub fn poll_event(&self, timeout: f32) -> PollEvent {
+        let timeout = if timeout.is_nan() || timeout <= 0.0 {
+            Some(Duration::ZERO)
+        } else {
+            Duration::try_from_secs_f32(timeout).ok()
+        };
+        match timeout {
+            Some(timeout) => self.poll_timeout(timeout),
+            None => self.poll_blocking(),
+        }
+    }
+
+    /// Poll with timeout, a timeout too large to represent blocks.
+    pub fn poll_timeout(&self, timeout: Duration) -> PollEvent {
+        self.wait(Instant::now().checked_add(timeout))
+    }
+
+    /// Poll until the deadline
+    pub fn poll_deadline(&self, deadline: Instant) -> PollEvent {
+        self.wait(Some(deadline))
     }
 
-    /// Poll with decimal seconds timeout, return channel id, -1 for timeout.
-    pub fn poll(&self, timeout: f32) -> i32 {
-        let timeout = Duration::from_nanos((timeout * 1e9) as u64);
-        // single reader
+    /// Poll until an event arrives, never returns [`PollEvent::Timeout`].
+    pub fn poll_blocking(&self) -> PollEvent {
+        self.wait(None)
+    }
+
+    /// Poll without blocking, [`PollEvent::Timeout`] if nothing is ready.
+    pub fn try_poll(&self) -> PollEvent {
+        self.wait(Some(Instant::now()))
+    }
+
+    /// Wait up to timeout for events, then take every pending one without blocking.
+    ///
+    /// `events` is cleared and filled with at most `max` distinct events in arrival
+    /// order, each with the number of times it was signaled, returns the number of
+    /// distinct events, 0 for timeout.
+    pub fn poll_many(
+        &self,
+        events: &mut Vec<(PollEvent, usize)>,
+        max: usize,
+        timeout: Duration,
+    ) -> usize {
+        events.clear();
+        if max == 0 {
+            return 0;
+        }
+        let mut index: HashMap<PollEvent, usize> = HashMap::new();
+        let mut deadline = Instant::now().checked_add(timeout);
+        while events.len() < max {
+            let event = self.wait(deadline);
+            if event == PollEvent::Timeout {
+                break;
+            }
+            match index.get(&event) {
+                Some(&i) => events[i].1 += 1,
+                None => {
+                    index.insert(event, events.len());
+                    events.push((event, 1));
+                }
+            }
+            // only the first one is waited for
+            deadline = Some(Instant::now());
+        }
+        events.len()
+    }
+
+    /// Register a file descriptor, or change the interest or token of a registered one.
+    ///
+    /// [`Poll::poll_token`] reports it with `token`, [`PollEvent::Io`] with the descriptor.
+    /// The poll switches to epoll once a descriptor is added, channel events wake
+    /// it through an eventfd. Descriptors are level triggered, a ready one is
+    /// reported by every poll until it's read or written.
+    #[cfg(target_os = "linux")]
+    pub fn add_fd(&self, fd: RawFd, interest: Interest, token: usize) -> std::io::Result<()> {
+        let mut guard = self.signal.lock().unwrap();
+        let signal = guard.as_mut().unwrap();
+        if let Some(epoll) = &signal.epoll {
+            return epoll.add(fd, interest, token);
+        }
+        let epoll = Arc::new(epoll::Epoll::new()?);
+        epoll.add(fd, interest, token)?;
+        signal.epoll = Some(epoll);
+        // a thread waiting on the channels switches to epoll, a task polls again
+        let _ = signal.tx.send(Notice::new(PollEvent::Timeout));
+        #[cfg(feature = "async")]
+        let wakers = signal.wakers.clone();
+        drop(guard);
+        #[cfg(feature = "async")]
+        wakers.wake();
+        Ok(())
+    }
+
+    /// Deregister a file descriptor, before it is closed.
+    #[cfg(target_os = "linux")]
+    pub fn remove_fd(&self, fd: RawFd) -> std::io::Result<()> {
+        let epoll = self.signal.lock().unwrap().as_ref().unwrap().epoll.clone();
+        match epoll {
+            Some(epoll) => epoll.remove(fd),
+            None => Ok(()),
+        }
+    }
+
+    /// Poll with timeout, return the token of the source and the event, None for timeout.
+    ///
+    /// The token is None for a source added without one, e.g. by [`Poll::add`].
+    pub fn poll_token(&self, timeout: Duration) -> Option<(Option<usize>, PollEvent)> {
+        self.next(Instant::now().checked_add(timeout))
+    }
+
+    fn wait(&self, deadline: Option<Instant>) -> PollEvent {
+        self.next(deadline)
+            .map_or(PollEvent::Timeout, |(_, event)| event)
+    }
+
+    /// Wait for the next event of a registered receiver, None deadline for ever.
+    fn next(&self, deadline: Option<Instant>) -> Option<(Option<usize>, PollEvent)> {
+        loop {
+            // don't hold the lock while waiting, senders need it to fetch the signal
+            let signal = self.signal.lock().unwrap();
+            let rx = signal.as_ref().unwrap().rx.clone();
+            // looked up again after a stale notice, add_fd sends one
+            #[cfg(target_os = "linux")]
+            if let Some(epoll) = signal.as_ref().unwrap().epoll.clone() {
+                drop(signal);
+                return self.wait_epoll(&rx, &epoll, deadline);
+            }
+            drop(signal);
+            // None for timeout
+            let notice = match deadline {
+                Some(deadline) => rx.recv_deadline(deadline).ok(),
+                // the poll owns a sender, recv never fails
+                None => rx.recv().ok(),
+            }?;
+            if let Some(event) = self.accept(notice) {
+                return Some(event);
+            }
+        }
+    }
+
+    /// Channel events first, then file descriptors
+    #[cfg(target_os = "linux")]
+    fn wait_epoll(
+        &self,
+        rx: &crossbeam::channel::Receiver<Notice>,
+        epoll: &epoll::Epoll,
+        deadline: Option<Instant>,
+    ) -> Option<(Option<usize>, PollEvent)> {
+        loop {
+            if let Some(event) = rx.try_iter().find_map(|notice| self.accept(notice)) {
+                return Some(event);
+            }
+            if let Some((fd, ready, token)) = epoll.wait(deadline).expect("epoll_wait") {
+                return Some((Some(token), PollEvent::Io(fd, ready)));
+            }
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                return rx.try_iter().find_map(|notice| self.accept(notice));
+            }
+        }
+    }
+
+    /// The token and event of a notice, None if stale
+    fn accept(&self, notice: Notice) -> Option<(Option<usize>, PollEvent)> {
+        // a Timeout notice only wakes the waiting thread
+        let id = notice.event.id()?;
+        // skip sources removed while their events were in flight
+        let sources = match notice.event {
+            PollEvent::Writable(_) => &self.senders,
+            _ => &self.receivers,
+        };
+        let token = sources.lock().unwrap().get(&id)?.token;
+        // and channels drained since
+        let valid = match (notice.shared, notice.pending) {
+            (Some(shared), Some(pending)) => shared.take(&pending),
+            (_, pending) => pending.is_none_or(|pending| pending.swap(false, Ordering::SeqCst)),
+        };
+        valid.then_some((token, notice.event))
+    }
+
+    #[cfg(feature = "async")]
+    fn wakers(&self) -> Arc<future::Wakers> {
+        self.signal.lock().unwrap().as_ref().unwrap().wakers.clone()
+    }
+
+    /// Drop queued events matching the filter
+    fn purge(&self, filter: impl Fn(&PollEvent) -> bool) {
         let signal = self.signal.lock().unwrap();
-        signal
-            .as_ref()
-            .unwrap()
-            .rx
-            .recv_timeout(timeout)
-            .unwrap_or(-1)
+        let signal = signal.as_ref().unwrap();
+        let notices: Vec<Notice> = signal.rx.try_iter().collect();
+        for notice in notices.into_iter().filter(|n| !filter(&n.event)) {
+            let _ = signal.tx.send(notice);
+        }
+    }
+}
+
+impl Drop for Poll {
+    fn drop(&mut self) {
+        self.clear();
+        #[cfg(all(target_os = "linux", feature = "async"))]
+        {
+            let signal = self.signal.lock().unwrap();
+            let signal = signal.as_ref().unwrap();
+            signal.watcher.stop(signal.epoll.as_deref());
+        }
+    }
+}
+
+/// Wait for a notification until the deadline, None deadline for ever.
+/// The guard back, None once the deadline passed.
+pub(crate) fn wait_until<'a, T>(
+    changed: &Condvar,
+    guard: MutexGuard<'a, T>,
+    deadline: Option<Instant>,
+) -> Option<MutexGuard<'a, T>> {
+    match deadline {
+        Some(deadline) => {
+            let left = deadline.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                return None;
+            }
+            Some(changed.wait_timeout(guard, left).unwrap().0)
+        }
+        None => Some(changed.wait(guard).unwrap()),
     }
 }
@@ -0,0 +1,151 @@
+use std::time::{Duration, Instant};
+
+use crate::Poll;
+
+/// Wait on the channels of a `select!`, false once the deadline passed.
+///
+/// Send arms get [`Writable`](crate::PollEvent::Writable) events, except on a
+/// rendezvous channel, which is retried every millisecond.
+pub fn select_wait(poll: &Poll, deadline: Option<Instant>, send: bool) -> bool {
+    let now = Instant::now();
+    if deadline.is_some_and(|deadline| now >= deadline) {
+        return false;
+    }
+    let retry = send.then(|| now + Duration::from_millis(1));
+    match (deadline, retry) {
+        (Some(deadline), Some(retry)) => poll.poll_deadline(deadline.min(retry)),
+        (Some(deadline), None) | (None, Some(deadline)) => poll.poll_deadline(deadline),
+        (None, None) => poll.poll_blocking(),
+    };
+    true
+}
+
+/// Wait on several channel operations, run the arm of the first one completed.
+///
+/// Supported arms, tried in order:
+///
+/// - `recv(rx) -> msg => body`, `msg` is `Result<T, RecvError>`
+/// - `send(tx, value) -> res => body`, `res` is `Result<(), SendError<T>>`
+/// - `default => body`, run if no operation is ready right away
+/// - `default(timeout) => body`, run if no operation completed within the `Duration`
+///
+/// Without a default arm it blocks until an operation completes. The channels are
+/// registered with a temporary [`Poll`](crate::Poll) meanwhile, alongside any
+/// other poll.
+///
+///```rust
+///use poll_channel::{channel, select};
+///use std::time::Duration;
+///
+///let (tx1, rx1) = channel::<i32>();
+///let (tx2, rx2) = channel::<i32>();
+///tx2.send(2).unwrap();
+///
+///let n = select! {
+///    recv(rx1) -> msg => msg.unwrap(),
+///    recv(rx2) -> msg => msg.unwrap() * 10,
+///    send(tx1, 1) -> res => { res.unwrap(); 0 }
+///    default(Duration::from_millis(10)) => -1,
+///};
+///assert!(n == 20);
+///```
+#[macro_export]
+macro_rules! select {
+    ($($tokens:tt)*) => {
+        $crate::__select_parse!((__poll __send) () () () () ; $($tokens)*)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __select_parse {
+    // recv arm, block body
+    (($poll:ident $send:ident) ($($decl:tt)*) ($($try:tt)*) ($($dispatch:tt)*) ($($default:tt)*) ;
+        recv($rx:expr) -> $pat:pat => $body:block $(, $($rest:tt)*)?) => {
+        $crate::__select_parse!(($poll $send)
+            ($($decl)* let __rx = &$rx; $poll.add(__rx); let mut __res = None;)
+            ($($try)* match __rx.try_recv() {
+                Ok(data) => { __res = Some(Ok(data)); break; }
+                Err($crate::TryRecvError::Disconnected) => { __res = Some(Err($crate::RecvError)); break; }
+                Err($crate::TryRecvError::Empty) => {}
+            })
+            ($($dispatch)* if let Some(res) = __res { let $pat = res; $body } else)
+            ($($default)*) ; $($($rest)*)?)
+    };
+    (($poll:ident $send:ident) $decl:tt $try:tt $dispatch:tt $default:tt ;
+        recv($rx:expr) -> $pat:pat => $body:block $($rest:tt)+) => {
+        $crate::__select_parse!(($poll $send) $decl $try $dispatch $default ;
+            recv($rx) -> $pat => $body, $($rest)+)
+    };
+    // recv arm, expression body
+    (($poll:ident $send:ident) $decl:tt $try:tt $dispatch:tt $default:tt ;
+        recv($rx:expr) -> $pat:pat => $body:expr $(, $($rest:tt)*)?) => {
+        $crate::__select_parse!(($poll $send) $decl $try $dispatch $default ;
+            recv($rx) -> $pat => { $body }, $($($rest)*)?)
+    };
+    // send arm, block body
+    (($poll:ident $send:ident) ($($decl:tt)*) ($($try:tt)*) ($($dispatch:tt)*) ($($default:tt)*) ;
+        send($tx:expr, $value:expr) -> $pat:pat => $body:block $(, $($rest:tt)*)?) => {
+        $crate::__select_parse!(($poll $send)
+            ($($decl)* let __tx = &$tx; let mut __value = Some($value); let mut __res = None;
+                $poll.add_sender(__tx); $send |= __tx.capacity() == Some(0);)
+            ($($try)* if let Some(value) = __value.take() {
+                match __tx.try_send(value) {
+                    Ok(()) => { __res = Some(Ok(())); break; }
+                    Err($crate::TrySendError::Disconnected(value)) => { __res = Some(Err($crate::SendError(value))); break; }
+                    Err($crate::TrySendError::Full(value)) => __value = Some(value),
+                }
+            })
+            ($($dispatch)* if let Some(res) = __res { let $pat = res; $body } else)
+            ($($default)*) ; $($($rest)*)?)
+    };
+    (($poll:ident $send:ident) $decl:tt $try:tt $dispatch:tt $default:tt ;
+        send($tx:expr, $value:expr) -> $pat:pat => $body:block $($rest:tt)+) => {
+        $crate::__select_parse!(($poll $send) $decl $try $dispatch $default ;
+            send($tx, $value) -> $pat => $body, $($rest)+)
+    };
+    // send arm, expression body
+    (($poll:ident $send:ident) $decl:tt $try:tt $dispatch:tt $default:tt ;
+        send($tx:expr, $value:expr) -> $pat:pat => $body:expr $(, $($rest:tt)*)?) => {
+        $crate::__select_parse!(($poll $send) $decl $try $dispatch $default ;
+            send($tx, $value) -> $pat => { $body }, $($($rest)*)?)
+    };
+    // default arm, without or with timeout
+    (($poll:ident $send:ident) $decl:tt $try:tt $dispatch:tt () ;
+        default => $($rest:tt)*) => {
+        $crate::__select_parse!(($poll $send) $decl $try $dispatch
+            (Some(::std::time::Instant::now())) ; => $($rest)*)
+    };
+    (($poll:ident $send:ident) $decl:tt $try:tt $dispatch:tt () ;
+        default($timeout:expr) => $($rest:tt)*) => {
+        $crate::__select_parse!(($poll $send) $decl $try $dispatch
+            (::std::time::Instant::now().checked_add($timeout)) ; => $($rest)*)
+    };
+    (($poll:ident $send:ident) $decl:tt $try:tt $dispatch:tt ($deadline:expr) ;
+        => $body:block $(,)?) => {
+        $crate::__select_parse!(($poll $send) $decl $try $dispatch ($deadline, $body) ;)
+    };
+    (($poll:ident $send:ident) $decl:tt $try:tt $dispatch:tt ($deadline:expr) ;
+        => $body:expr $(,)?) => {
+        $crate::__select_parse!(($poll $send) $decl $try $dispatch ($deadline, { $body }) ;)
+    };
+    // done
+    (($poll:ident $send:ident) ($($decl:tt)*) ($($try:tt)*) ($($dispatch:tt)*) () ;) => {
+        $crate::__select_parse!(($poll $send) ($($decl)*) ($($try)*) ($($dispatch)*)
+            (None, { unreachable!() }) ;)
+    };
+    (($poll:ident $send:ident) ($($decl:tt)*) ($($try:tt)*) ($($dispatch:tt)*) ($deadline:expr, $default:block) ;) => {{
+        let $poll = $crate::Poll::new();
+        #[allow(unused_mut)]
+        let mut $send = false;
+        $($decl)*
+        let deadline: Option<::std::time::Instant> = $deadline;
+        loop {
+            $($try)*
+            if !$crate::__select_wait(&$poll, deadline, $send) {
+                break;
+            }
+        }
+        $($dispatch)* $default
+    }};
+}
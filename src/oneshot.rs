@@ -0,0 +1,173 @@
+//! Oneshot channel, a single message from a sender consumed by `send`.
+//!
+//! Dropping the sender without sending reports [`PollEvent::Cancelled`].
+//!
+//!```rust
+//!use poll_channel::{oneshot, Poll, PollEvent};
+//!
+//!let (tx1, rx1) = oneshot();
+//!let (tx2, rx2) = oneshot::<i32>();
+//!let poller = Poll::new();
+//!poller.append(&[&rx1, &rx2]);
+//!
+//!tx1.send(42).unwrap();
+//!drop(tx2);
+//!assert!(poller.try_poll() == PollEvent::Ready(rx1.id()));
+//!assert!(poller.try_poll() == PollEvent::Cancelled(rx2.id()));
+//!assert!(rx1.recv().unwrap() == 42);
+//!assert!(rx2.recv().is_err());
+//!```
+//!
+//! [`PollEvent::Cancelled`]: crate::PollEvent::Cancelled
+use std::{
+    sync::{Arc, Condvar, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    wait_until, ChannelId, Pollable, RecvError, RecvTimeoutError, SendError, Shared, TryRecvError,
+    Waker,
+};
+
+/// Create a oneshot channel.
+pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
+    let inner = Arc::new(Inner {
+        state: Mutex::new(State::Empty),
+        changed: Condvar::new(),
+        shared: Shared::new(),
+    });
+    (
+        Sender {
+            inner: inner.clone(),
+        },
+        Receiver { inner },
+    )
+}
+
+enum State<T> {
+    Empty,
+    Full(T),
+    /// the message was received
+    Taken,
+    /// the sender was dropped without sending
+    Cancelled,
+    /// the receiver was dropped
+    Closed,
+}
+
+struct Inner<T> {
+    state: Mutex<State<T>>,
+    changed: Condvar,
+    shared: Arc<Shared>,
+}
+
+pub struct Sender<T> {
+    inner: Arc<Inner<T>>,
+}
+
+pub struct Receiver<T> {
+    inner: Arc<Inner<T>>,
+}
+
+impl<T> Sender<T> {
+    /// Send the message, fails if the receiver was dropped.
+    pub fn send(self, data: T) -> Result<(), SendError<T>> {
+        let mut state = self.inner.state.lock().unwrap();
+        if let State::Closed = *state {
+            return Err(SendError(data));
+        }
+        *state = State::Full(data);
+        self.inner.shared.sent();
+        self.inner.changed.notify_all();
+        Ok(())
+    }
+
+    pub fn is_closed(&self) -> bool {
+        matches!(*self.inner.state.lock().unwrap(), State::Closed)
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut state = self.inner.state.lock().unwrap();
+        if let State::Empty = *state {
+            *state = State::Cancelled;
+            self.inner.shared.cancel();
+            self.inner.changed.notify_all();
+        }
+    }
+}
+
+impl<T> Receiver<T> {
+    pub fn id(&self) -> ChannelId {
+        self.inner.shared.id()
+    }
+
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let mut state = self.inner.state.lock().unwrap();
+        self.take(&mut state)
+    }
+
+    pub fn recv(&self) -> Result<T, RecvError> {
+        let mut state = self.inner.state.lock().unwrap();
+        loop {
+            match self.take(&mut state) {
+                Ok(data) => return Ok(data),
+                Err(TryRecvError::Disconnected) => return Err(RecvError),
+                Err(TryRecvError::Empty) => state = self.inner.changed.wait(state).unwrap(),
+            }
+        }
+    }
+
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        let deadline = Instant::now().checked_add(timeout);
+        let mut state = self.inner.state.lock().unwrap();
+        loop {
+            match self.take(&mut state) {
+                Ok(data) => return Ok(data),
+                Err(TryRecvError::Disconnected) => return Err(RecvTimeoutError::Disconnected),
+                Err(TryRecvError::Empty) => {}
+            }
+            state = wait_until(&self.inner.changed, state, deadline)
+                .ok_or(RecvTimeoutError::Timeout)?;
+        }
+    }
+
+    /// The sender was dropped without sending
+    pub fn is_cancelled(&self) -> bool {
+        matches!(*self.inner.state.lock().unwrap(), State::Cancelled)
+    }
+
+    fn take(&self, state: &mut MutexGuard<State<T>>) -> Result<T, TryRecvError> {
+        match std::mem::replace(&mut **state, State::Taken) {
+            State::Full(data) => {
+                self.inner.shared.received();
+                Ok(data)
+            }
+            State::Empty => {
+                **state = State::Empty;
+                Err(TryRecvError::Empty)
+            }
+            other => {
+                **state = other;
+                Err(TryRecvError::Disconnected)
+            }
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        *self.inner.state.lock().unwrap() = State::Closed;
+    }
+}
+
+impl<T> Pollable for Receiver<T> {
+    fn id(&self) -> ChannelId {
+        self.inner.shared.id()
+    }
+
+    fn register(&self, waker: Waker) {
+        self.inner.shared.register(0, waker);
+    }
+}
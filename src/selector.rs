@@ -0,0 +1,134 @@
+use std::{collections::HashMap, time::Duration};
+
+use crate::{ChannelId, Poll, PollEvent, Receiver, RecvError, TryRecvError};
+
+/// Receive a message, None if there is nothing to receive, Some(false) once disconnected
+type Handler<'a> = Box<dyn FnMut() -> Option<bool> + 'a>;
+
+/// Receive from whichever registered receiver is ready and run its handler.
+///
+/// The receivers are registered with the selector's own [`Poll`], other polls
+/// they are registered with keep getting their events.
+///
+///```rust
+///use poll_channel::{channel, Selector};
+///use std::time::Duration;
+///
+///let (tx1, rx1) = channel();
+///let (tx2, rx2) = channel();
+///let mut total = 0;
+///let mut names = Vec::new();
+///{
+///    let mut selector = Selector::new();
+///    selector
+///        .recv(&rx1, |n: Result<i32, _>| total += n.unwrap())
+///        .recv(&rx2, |s: Result<&str, _>| names.push(s.unwrap()));
+///
+///    tx1.send(1).unwrap();
+///    tx2.send("one").unwrap();
+///    while selector.select(Duration::from_millis(10)).is_some() {}
+///}
+///assert!(total == 1 && names == ["one"]);
+///```
+pub struct Selector<'a> {
+    poll: Poll,
+    handlers: HashMap<ChannelId, Handler<'a>>,
+    /// disconnected with messages left, the poll won't report them again
+    closed: Vec<ChannelId>,
+}
+
+impl Default for Selector<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Selector<'a> {
+    pub fn new() -> Self {
+        Self {
+            poll: Poll::new(),
+            handlers: HashMap::new(),
+            closed: Vec::new(),
+        }
+    }
+
+    /// Register a receiver with its handler, replacing the previous handler of the receiver.
+    ///
+    /// The handler is given `Err(RecvError)` once the channel is disconnected and
+    /// drained, the receiver is removed afterwards.
+    pub fn recv<T, F>(&mut self, receiver: &'a Receiver<T>, mut handler: F) -> &mut Self
+    where
+        F: FnMut(Result<T, RecvError>) + 'a,
+    {
+        let op = move || match receiver.try_recv() {
+            Ok(data) => {
+                handler(Ok(data));
+                Some(true)
+            }
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => {
+                handler(Err(RecvError));
+                Some(false)
+            }
+        };
+        self.handlers.insert(receiver.id(), Box::new(op));
+        self.poll.add(receiver);
+        self
+    }
+
+    /// Remove a receiver and its handler
+    pub fn remove<T>(&mut self, receiver: &Receiver<T>) {
+        self.handlers.remove(&receiver.id());
+        self.closed.retain(|id| *id != receiver.id());
+        self.poll.remove(receiver);
+    }
+
+    /// Number of registered receivers
+    pub fn len(&self) -> usize {
+        self.handlers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.handlers.is_empty()
+    }
+
+    /// Receive one message and run its handler, return the channel id, None for timeout.
+    pub fn select(&mut self, timeout: Duration) -> Option<ChannelId> {
+        let deadline = std::time::Instant::now().checked_add(timeout);
+        loop {
+            let (id, disconnected) = match self.closed.first() {
+                Some(id) => (*id, true),
+                None => {
+                    let event = match deadline {
+                        Some(deadline) => self.poll.poll_deadline(deadline),
+                        None => self.poll.poll_blocking(),
+                    };
+                    match event {
+                        PollEvent::Ready(id) => (id, false),
+                        PollEvent::Disconnected(id) => (id, true),
+                        _ => return None,
+                    }
+                }
+            };
+            let Some(handler) = self.handlers.get_mut(&id) else {
+                continue;
+            };
+            match handler() {
+                Some(true) => {
+                    if disconnected && !self.closed.contains(&id) {
+                        self.closed.push(id);
+                    }
+                    return Some(id);
+                }
+                Some(false) => {
+                    self.handlers.remove(&id);
+                    self.closed.retain(|i| *i != id);
+                    self.poll.forget(id);
+                    return Some(id);
+                }
+                // taken by someone else
+                None => continue,
+            }
+        }
+    }
+}
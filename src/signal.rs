@@ -0,0 +1,197 @@
+use std::{
+    collections::{btree_map::Entry, BTreeMap},
+    io,
+    sync::{
+        atomic::{AtomicI32, Ordering},
+        Mutex,
+    },
+};
+
+use crate::{channel, Receiver, Sender};
+
+pub use libc::{SIGHUP, SIGINT, SIGQUIT, SIGTERM, SIGUSR1, SIGUSR2};
+
+/// Receive the numbers of the given unix signals as they are delivered.
+///
+/// The handler only writes to a pipe, a thread forwards the signals to the
+/// channels. Handlers installed before for these signals are replaced, the
+/// signals are no longer handled by default, e.g. SIGTERM doesn't terminate.
+/// Once every receiver of a signal is dropped, the previous action is put back
+/// on its next delivery and handles it.
+///
+///```no_run
+///use poll_channel::{signals, Poll, PollEvent, SIGINT, SIGTERM};
+///
+///let rx = signals(&[SIGINT, SIGTERM]).unwrap();
+///let poller = Poll::new();
+///poller.add(&rx);
+///if let PollEvent::Ready(_) = poller.poll_blocking() {
+///    println!("shutdown on {}", rx.recv().unwrap());
+///}
+///```
+pub fn signals(signals: &[i32]) -> io::Result<Receiver<i32>> {
+    let (tx, rx) = channel();
+    let mut subscribers = SUBSCRIBERS.lock().unwrap();
+    if PIPE.load(Ordering::Relaxed) < 0 {
+        start()?;
+    }
+    for &signal in signals {
+        let subscription = match subscribers.entry(signal) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(Subscription {
+                senders: Vec::new(),
+                previous: install(signal)?,
+            }),
+        };
+        subscription.senders.push(tx.clone());
+    }
+    Ok(rx)
+}
+
+/// Receivers of a signal, and the action the handler replaced
+struct Subscription {
+    senders: Vec<Sender<i32>>,
+    previous: libc::sigaction,
+}
+
+static SUBSCRIBERS: Mutex<BTreeMap<i32, Subscription>> = Mutex::new(BTreeMap::new());
+
+/// write end of the self pipe, used by the handler
+static PIPE: AtomicI32 = AtomicI32::new(-1);
+
+extern "C" fn handler(signal: libc::c_int) {
+    let byte = signal as u8;
+    // SAFETY: write is async signal safe, a full pipe drops the signal, errno
+    // of the interrupted code is restored
+    unsafe {
+        let saved = io::Error::last_os_error().raw_os_error().unwrap_or(0);
+        libc::write(
+            PIPE.load(Ordering::Relaxed),
+            &byte as *const u8 as *const _,
+            1,
+        );
+        set_errno(saved);
+    }
+}
+
+/// Set errno of the calling thread, left as is where its location is unknown
+unsafe fn set_errno(errno: libc::c_int) {
+    let _ = errno;
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "l4re",
+        target_os = "emscripten",
+        target_os = "redox",
+        target_os = "hurd",
+        target_os = "dragonfly",
+        target_os = "fuchsia"
+    ))]
+    {
+        *libc::__errno_location() = errno;
+    }
+    #[cfg(any(target_vendor = "apple", target_os = "freebsd"))]
+    {
+        *libc::__error() = errno;
+    }
+    #[cfg(any(
+        target_os = "android",
+        target_os = "netbsd",
+        target_os = "openbsd",
+        target_os = "cygwin",
+        target_os = "nuttx",
+        target_env = "newlib"
+    ))]
+    {
+        *libc::__errno() = errno;
+    }
+    #[cfg(any(target_os = "solaris", target_os = "illumos"))]
+    {
+        *libc::___errno() = errno;
+    }
+    #[cfg(target_os = "haiku")]
+    {
+        *libc::_errnop() = errno;
+    }
+    #[cfg(target_os = "aix")]
+    {
+        *libc::_Errno() = errno;
+    }
+    #[cfg(target_os = "nto")]
+    {
+        *libc::__get_errno_ptr() = errno;
+    }
+    #[cfg(target_os = "vxworks")]
+    {
+        libc::errnoSet(errno);
+    }
+}
+
+/// Install the handler, return the action it replaces
+fn install(signal: i32) -> io::Result<libc::sigaction> {
+    // SAFETY: zeroed sigaction is valid, the handler only calls write
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = handler as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        action.sa_flags = libc::SA_RESTART;
+        libc::sigemptyset(&mut action.sa_mask);
+        let mut previous: libc::sigaction = std::mem::zeroed();
+        if libc::sigaction(signal, &action, &mut previous) < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(previous)
+    }
+}
+
+/// Create the pipe and the forwarding thread
+fn start() -> io::Result<()> {
+    let mut fds = [0; 2];
+    // SAFETY: fds has room for both ends
+    if unsafe { libc::pipe(fds.as_mut_ptr()) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let [read, write] = fds;
+    // SAFETY: flags on descriptors we own
+    unsafe {
+        libc::fcntl(read, libc::F_SETFD, libc::FD_CLOEXEC);
+        libc::fcntl(write, libc::F_SETFD, libc::FD_CLOEXEC);
+        // the handler must never block
+        libc::fcntl(write, libc::F_SETFL, libc::O_NONBLOCK);
+    }
+    std::thread::Builder::new()
+        .name("poll-channel-signal".into())
+        .spawn(move || forward(read))?;
+    PIPE.store(write, Ordering::Relaxed);
+    Ok(())
+}
+
+fn forward(read: libc::c_int) {
+    let mut buf = [0u8; 64];
+    loop {
+        // SAFETY: reads into buf, the read end is never closed
+        let n = unsafe { libc::read(read, buf.as_mut_ptr() as *mut _, buf.len()) };
+        if n < 0 && io::Error::last_os_error().kind() == io::ErrorKind::Interrupted {
+            continue;
+        }
+        if n <= 0 {
+            return;
+        }
+        let mut subscribers = SUBSCRIBERS.lock().unwrap();
+        for &signal in &buf[..n as usize] {
+            let signal = signal as i32;
+            let Some(subscription) = subscribers.get_mut(&signal) else {
+                continue;
+            };
+            // forget receivers which were dropped
+            subscription.senders.retain(|tx| tx.send(signal).is_ok());
+            if subscription.senders.is_empty() {
+                let subscription = subscribers.remove(&signal).unwrap();
+                // SAFETY: the action was returned by sigaction, the signal is
+                // delivered again to it
+                unsafe {
+                    libc::sigaction(signal, &subscription.previous, std::ptr::null_mut());
+                    libc::kill(libc::getpid(), signal);
+                }
+            }
+        }
+    }
+}
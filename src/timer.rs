@@ -0,0 +1,166 @@
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    sync::{Condvar, Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+
+use crate::{bounded, Receiver, Sender, TrySendError};
+
+/// Receive the time once, after the duration.
+///
+/// The channel is disconnected once fired, a poll reports the
+/// disconnection after the message so the timer can be retired.
+pub fn after(duration: Duration) -> Receiver<Instant> {
+    match Instant::now().checked_add(duration) {
+        Some(deadline) => at(deadline),
+        None => never(),
+    }
+}
+
+/// Receive the time once, at the deadline, fires right away if it's passed.
+///
+/// Disconnected once fired, like [`after`].
+pub fn at(deadline: Instant) -> Receiver<Instant> {
+    let (tx, rx) = bounded(1);
+    timer().schedule(Entry {
+        deadline,
+        period: None,
+        tx,
+    });
+    rx
+}
+
+/// Shortest period of [`tick`]
+const MIN_PERIOD: Duration = Duration::from_millis(1);
+
+/// Receive the time periodically, every duration, at least a millisecond.
+///
+/// A tick is dropped if the previous one wasn't received yet. The timer stops
+/// once the receiver is dropped.
+pub fn tick(duration: Duration) -> Receiver<Instant> {
+    let duration = duration.max(MIN_PERIOD);
+    let Some(deadline) = Instant::now().checked_add(duration) else {
+        return never();
+    };
+    let (tx, rx) = bounded(1);
+    timer().schedule(Entry {
+        deadline,
+        period: Some(duration),
+        tx,
+    });
+    rx
+}
+
+/// A receiver which never fires, the sender is kept for ever.
+fn never() -> Receiver<Instant> {
+    let (tx, rx) = bounded(1);
+    std::mem::forget(tx);
+    rx
+}
+
+struct Entry {
+    deadline: Instant,
+    period: Option<Duration>,
+    tx: Sender<Instant>,
+}
+
+impl PartialEq for Entry {
+    fn eq(&self, other: &Self) -> bool {
+        self.deadline == other.deadline
+    }
+}
+
+impl Eq for Entry {}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Entry {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.deadline.cmp(&other.deadline)
+    }
+}
+
+/// Timers of the process, fired by a single thread
+struct Timer {
+    entries: Mutex<BinaryHeap<Reverse<Entry>>>,
+    changed: Condvar,
+}
+
+fn timer() -> &'static Timer {
+    static TIMER: OnceLock<Timer> = OnceLock::new();
+    TIMER.get_or_init(|| {
+        std::thread::Builder::new()
+            .name("poll-channel-timer".into())
+            .spawn(|| timer().run())
+            .expect("spawn timer thread");
+        Timer {
+            entries: Mutex::new(BinaryHeap::new()),
+            changed: Condvar::new(),
+        }
+    })
+}
+
+impl Timer {
+    fn schedule(&self, entry: Entry) {
+        let mut entries = self.entries.lock().unwrap();
+        // the thread only needs to wake for a new earliest deadline
+        let earliest = entries
+            .peek()
+            .is_none_or(|first| entry.deadline < first.0.deadline);
+        entries.push(Reverse(entry));
+        if earliest {
+            self.changed.notify_one();
+        }
+    }
+
+    fn run(&self) {
+        loop {
+            let (mut entry, now) = self.next();
+            // fired without the lock, schedule isn't held up by the polls
+            let result = entry.tx.try_send(now);
+            let Some(period) = entry.period else {
+                // once, the sender is dropped
+                continue;
+            };
+            if let Err(TrySendError::Disconnected(_)) = result {
+                continue;
+            }
+            // skip the ticks missed
+            entry.deadline += period;
+            if entry.deadline <= now {
+                entry.deadline = now + period;
+            }
+            self.entries.lock().unwrap().push(Reverse(entry));
+        }
+    }
+
+    /// Wait for the earliest entry to be due and take it
+    fn next(&self) -> (Entry, Instant) {
+        let mut entries = self.entries.lock().unwrap();
+        loop {
+            let now = Instant::now();
+            let deadline = match entries.peek() {
+                Some(first) => first.0.deadline,
+                None => {
+                    entries = self.changed.wait(entries).unwrap();
+                    continue;
+                }
+            };
+            if deadline > now {
+                entries = self
+                    .changed
+                    .wait_timeout(entries, deadline - now)
+                    .unwrap()
+                    .0;
+                continue;
+            }
+            let Reverse(entry) = entries.pop().unwrap();
+            return (entry, now);
+        }
+    }
+}
@@ -0,0 +1,220 @@
+//! Watch channel, only the latest value is kept.
+//!
+//! The receiver is ready once the value changed since it was last seen, many
+//! sends before the receiver looks wake the poll once.
+//!
+//!```rust
+//!use poll_channel::{watch, Poll, PollEvent};
+//!
+//!let (tx, rx) = watch::channel(0);
+//!let poller = Poll::new();
+//!poller.add(&rx);
+//!
+//!for n in 1..=3 {
+//!    tx.send(n).unwrap();
+//!}
+//!assert!(poller.try_poll() == PollEvent::Ready(rx.id()));
+//!assert!(poller.try_poll() == PollEvent::Timeout);
+//!assert!(*rx.borrow_and_update() == 3);
+//!```
+use std::{
+    ops::Deref,
+    sync::{Arc, Condvar, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    wait_until, ChannelId, Pollable, RecvError, RecvTimeoutError, SendError, Shared, Waker,
+};
+
+/// Create a watch channel holding `initial`, which counts as seen.
+pub fn channel<T>(initial: T) -> (Sender<T>, Receiver<T>) {
+    let inner = Arc::new(Inner {
+        state: Mutex::new(State {
+            value: initial,
+            changed: false,
+            senders: 1,
+            closed: false,
+        }),
+        changed: Condvar::new(),
+        shared: Shared::new(),
+    });
+    (
+        Sender {
+            inner: inner.clone(),
+        },
+        Receiver { inner },
+    )
+}
+
+struct State<T> {
+    value: T,
+    /// not seen by the receiver yet
+    changed: bool,
+    senders: usize,
+    /// receiver dropped
+    closed: bool,
+}
+
+struct Inner<T> {
+    state: Mutex<State<T>>,
+    changed: Condvar,
+    shared: Arc<Shared>,
+}
+
+pub struct Sender<T> {
+    inner: Arc<Inner<T>>,
+}
+
+pub struct Receiver<T> {
+    inner: Arc<Inner<T>>,
+}
+
+/// Borrowed value, senders block until it is dropped.
+pub struct Ref<'a, T> {
+    state: MutexGuard<'a, State<T>>,
+}
+
+impl<T> Deref for Ref<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.state.value
+    }
+}
+
+impl<T> Sender<T> {
+    /// Replace the value, fails if the receiver was dropped.
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        self.send_replace(value).map(|_| ())
+    }
+
+    /// Replace the value and return the old one.
+    pub fn send_replace(&self, value: T) -> Result<T, SendError<T>> {
+        let mut state = self.inner.state.lock().unwrap();
+        if state.closed {
+            return Err(SendError(value));
+        }
+        let old = std::mem::replace(&mut state.value, value);
+        if !state.changed {
+            state.changed = true;
+            self.inner.shared.sent();
+        }
+        self.inner.changed.notify_all();
+        Ok(old)
+    }
+
+    /// Current value
+    pub fn borrow(&self) -> Ref<'_, T> {
+        Ref {
+            state: self.inner.state.lock().unwrap(),
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.inner.state.lock().unwrap().closed
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.inner.state.lock().unwrap().senders += 1;
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut state = self.inner.state.lock().unwrap();
+        state.senders -= 1;
+        if state.senders == 0 {
+            self.inner.shared.disconnect();
+            self.inner.changed.notify_all();
+        }
+    }
+}
+
+impl<T> Receiver<T> {
+    pub fn id(&self) -> ChannelId {
+        self.inner.shared.id()
+    }
+
+    /// Current value, without marking it seen
+    pub fn borrow(&self) -> Ref<'_, T> {
+        Ref {
+            state: self.inner.state.lock().unwrap(),
+        }
+    }
+
+    /// Current value, marked seen
+    pub fn borrow_and_update(&self) -> Ref<'_, T> {
+        let mut state = self.inner.state.lock().unwrap();
+        self.seen(&mut state);
+        Ref { state }
+    }
+
+    /// Whether the value changed since last seen, fails if all senders are gone.
+    pub fn has_changed(&self) -> Result<bool, RecvError> {
+        let state = self.inner.state.lock().unwrap();
+        match state.changed {
+            false if state.senders == 0 => Err(RecvError),
+            changed => Ok(changed),
+        }
+    }
+
+    /// Block until the value changed and mark it seen, fails if all senders are gone.
+    pub fn changed(&self) -> Result<(), RecvError> {
+        let mut state = self.inner.state.lock().unwrap();
+        loop {
+            if state.changed {
+                self.seen(&mut state);
+                return Ok(());
+            }
+            if state.senders == 0 {
+                return Err(RecvError);
+            }
+            state = self.inner.changed.wait(state).unwrap();
+        }
+    }
+
+    pub fn changed_timeout(&self, timeout: Duration) -> Result<(), RecvTimeoutError> {
+        let deadline = Instant::now().checked_add(timeout);
+        let mut state = self.inner.state.lock().unwrap();
+        loop {
+            if state.changed {
+                self.seen(&mut state);
+                return Ok(());
+            }
+            if state.senders == 0 {
+                return Err(RecvTimeoutError::Disconnected);
+            }
+            state = wait_until(&self.inner.changed, state, deadline)
+                .ok_or(RecvTimeoutError::Timeout)?;
+        }
+    }
+
+    fn seen(&self, state: &mut State<T>) {
+        if state.changed {
+            state.changed = false;
+            self.inner.shared.received();
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.inner.state.lock().unwrap().closed = true;
+    }
+}
+
+impl<T> Pollable for Receiver<T> {
+    fn id(&self) -> ChannelId {
+        self.inner.shared.id()
+    }
+
+    fn register(&self, waker: Waker) {
+        self.inner.shared.register(0, waker);
+    }
+}
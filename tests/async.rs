@@ -0,0 +1,145 @@
+#![cfg(feature = "async")]
+
+use futures_core::Stream;
+use poll_channel::{channel, Poll, PollEvent};
+use std::{
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll as TaskPoll},
+    time::Duration,
+};
+
+/// Minimal StreamExt::next
+async fn next<S: Stream + Unpin>(stream: &mut S) -> Option<S::Item> {
+    std::future::poll_fn(|cx: &mut Context<'_>| Pin::new(&mut *stream).poll_next(cx)).await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn recv_async_test() {
+    let (tx, rx) = channel();
+
+    let bg = std::thread::spawn(move || {
+        for i in 0..3 {
+            std::thread::sleep(Duration::from_millis(10));
+            tx.send(i).unwrap();
+        }
+    });
+
+    for i in 0..3 {
+        assert!(rx.recv_async().await.unwrap() == i);
+    }
+    assert!(rx.recv_async().await.is_err());
+    let _ = bg.join();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn stream_test() {
+    let (tx, mut rx) = channel();
+
+    let task = tokio::spawn(async move {
+        let mut all = Vec::new();
+        while let Some(n) = next(&mut rx).await {
+            all.push(n);
+        }
+        all
+    });
+
+    std::thread::spawn(move || {
+        for i in 0..100 {
+            tx.send(i).unwrap();
+        }
+    });
+
+    assert!(task.await.unwrap() == (0..100).collect::<Vec<_>>());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn next_event_test() {
+    let (tx1, rx1) = channel();
+    let (tx2, rx2) = channel::<i32>();
+    let poller = Arc::new(Poll::new());
+    poller.append(&[&rx1, &rx2]);
+
+    let bg = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(20));
+        tx1.send(1).unwrap();
+        drop(tx2);
+    });
+
+    assert!(poller.next_event().await == PollEvent::Ready(rx1.id()));
+    // received from a sync call
+    assert!(rx1.recv().unwrap() == 1);
+    assert!(poller.next_event().await == PollEvent::Disconnected(rx2.id()));
+    let _ = bg.join();
+    assert!(poller.next_event().await == PollEvent::Disconnected(rx1.id()));
+
+    // nothing else
+    let waker = std::task::Waker::noop();
+    let mut cx = Context::from_waker(waker);
+    let mut event = poller.next_event();
+    let pending = std::future::Future::poll(Pin::new(&mut event), &mut cx);
+    assert!(matches!(pending, TaskPoll::Pending));
+}
+
+/// Polls the next event as soon as it's woken, like an inline executor
+struct Inline {
+    poll: Arc<Poll>,
+    events: std::sync::Mutex<Vec<PollEvent>>,
+}
+
+impl std::task::Wake for Inline {
+    fn wake(self: Arc<Self>) {
+        let event = self.poll.try_poll();
+        self.events.lock().unwrap().push(event);
+    }
+}
+
+#[test]
+fn wake_inline_test() {
+    let (tx, rx) = channel();
+    let poll = Arc::new(Poll::new());
+    poll.add(&rx);
+    let inline = Arc::new(Inline {
+        poll: poll.clone(),
+        events: Default::default(),
+    });
+    let waker = std::task::Waker::from(inline.clone());
+    let mut cx = Context::from_waker(&waker);
+    let mut next = std::pin::pin!(poll.next_event());
+    assert!(std::future::Future::poll(next.as_mut(), &mut cx).is_pending());
+
+    // the waker polls while the send is in progress
+    tx.send(1).unwrap();
+    assert!(*inline.events.lock().unwrap() == vec![PollEvent::Ready(rx.id())]);
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test(flavor = "multi_thread")]
+async fn next_event_fd_test() {
+    use poll_channel::Interest;
+    use std::{
+        io::{Read, Write},
+        os::{fd::AsRawFd, unix::net::UnixStream},
+    };
+
+    let (mut a, mut b) = UnixStream::pair().unwrap();
+    let (tx, rx) = channel();
+    let poller = Arc::new(Poll::new());
+    poller.add(&rx);
+    poller.add_fd(b.as_raw_fd(), Interest::READABLE, 0).unwrap();
+
+    let bg = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(20));
+        a.write_all(b"ping").unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+        tx.send(1).unwrap();
+        a
+    });
+    let ready = PollEvent::Io(b.as_raw_fd(), Interest::READABLE);
+    assert!(poller.next_event().await == ready);
+    let mut buf = [0; 4];
+    b.read_exact(&mut buf).unwrap();
+    assert!(poller.next_event().await == PollEvent::Ready(rx.id()));
+    assert!(rx.recv().unwrap() == 1);
+    let _a = bg.join().unwrap();
+}
@@ -0,0 +1,79 @@
+use poll_channel::{broadcast, broadcast::RecvError, broadcast::TryRecvError, Poll, PollEvent};
+use std::time::Duration;
+
+#[test]
+fn broadcast_test() {
+    let (tx, rx1) = broadcast(16);
+    let rx2 = tx.subscribe();
+    assert!(rx1.id() != rx2.id());
+    assert!(tx.receiver_count() == 2);
+
+    // each subscriber in its own poll
+    let first = Poll::new();
+    let second = Poll::new();
+    first.add(&rx1);
+    second.add(&rx2);
+
+    assert!(tx.send(1).unwrap() == 2);
+    tx.send(2).unwrap();
+    assert!(first.try_poll() == PollEvent::Ready(rx1.id()));
+    assert!(second.try_poll() == PollEvent::Ready(rx2.id()));
+    assert!(first.try_poll() == PollEvent::Timeout);
+
+    for n in 1..3 {
+        assert!(rx1.recv().unwrap() == n);
+    }
+    assert!(rx1.try_recv() == Err(TryRecvError::Empty));
+    assert!(first.try_poll() == PollEvent::Timeout);
+    assert!(rx2.len() == 2);
+    assert!(rx2.recv().unwrap() == 1);
+    assert!(second.try_poll() == PollEvent::Ready(rx2.id()));
+    assert!(rx2.recv().unwrap() == 2);
+
+    // late subscriber only sees new messages
+    let rx3 = tx.subscribe();
+    assert!(rx3.is_empty());
+    drop(rx2);
+    assert!(tx.receiver_count() == 2);
+    drop(rx1);
+    drop(rx3);
+    assert!(tx.send(3).is_err());
+}
+
+#[test]
+fn lagged_test() {
+    let (tx, rx) = broadcast(2);
+    let poller = Poll::new();
+    poller.add(&rx);
+
+    for n in 0..5 {
+        tx.send(n).unwrap();
+    }
+    assert!(rx.len() == 5);
+    assert!(rx.recv() == Err(RecvError::Lagged(3)));
+    assert!(poller.try_poll() == PollEvent::Ready(rx.id()));
+    assert!(rx.recv().unwrap() == 3);
+    assert!(rx.recv().unwrap() == 4);
+    assert!(poller.try_poll() == PollEvent::Timeout);
+}
+
+#[test]
+fn broadcast_disconnect_test() {
+    let (tx, rx) = broadcast(4);
+    let poller = Poll::new();
+    poller.add(&rx);
+
+    let tx2 = tx.clone();
+    let bg = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(20));
+        tx2.send("hello").unwrap();
+    });
+    assert!(rx.recv_timeout(Duration::from_secs(1)).unwrap() == "hello");
+    let _ = bg.join();
+    // already received, nothing to report
+    assert!(poller.try_poll() == PollEvent::Timeout);
+
+    drop(tx);
+    assert!(poller.poll_event(0.1) == PollEvent::Disconnected(rx.id()));
+    assert!(rx.recv() == Err(RecvError::Disconnected));
+}
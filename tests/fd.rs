@@ -0,0 +1,72 @@
+#![cfg(target_os = "linux")]
+
+use poll_channel::{channel, Interest, Poll, PollEvent};
+use std::{
+    io::{Read, Write},
+    os::{fd::AsRawFd, unix::net::UnixStream},
+    sync::Arc,
+    time::Duration,
+};
+
+#[test]
+fn fd_test() {
+    let (mut a, mut b) = UnixStream::pair().unwrap();
+    let (tx, rx) = channel();
+    let poller = Poll::new();
+    poller.add(&rx);
+    poller.add_fd(b.as_raw_fd(), Interest::READABLE, 0).unwrap();
+    assert!(poller.poll_timeout(Duration::from_millis(10)) == PollEvent::Timeout);
+
+    a.write_all(b"ping").unwrap();
+    let ready = PollEvent::Io(b.as_raw_fd(), Interest::READABLE);
+    assert!(poller.poll_timeout(Duration::from_millis(100)) == ready);
+    // level triggered until read
+    assert!(poller.try_poll() == ready);
+    assert!(poller.poll(0.0) == -2 - i64::from(b.as_raw_fd()));
+    assert!(poller.poll_token(Duration::ZERO) == Some((Some(0), ready)));
+    let mut buf = [0; 4];
+    b.read_exact(&mut buf).unwrap();
+    assert!(poller.try_poll() == PollEvent::Timeout);
+
+    // a channel wakes the epoll
+    let bg = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(20));
+        tx.send(1).unwrap();
+    });
+    assert!(poller.poll_blocking() == PollEvent::Ready(rx.id()));
+    assert!(rx.recv().unwrap() == 1);
+    let _ = bg.join();
+    assert!(poller.poll_blocking() == PollEvent::Disconnected(rx.id()));
+
+    // writable, then removed
+    poller
+        .add_fd(b.as_raw_fd(), Interest::READABLE | Interest::WRITABLE, 0)
+        .unwrap();
+    let event = poller.try_poll();
+    assert!(event == PollEvent::Io(b.as_raw_fd(), Interest::WRITABLE));
+    poller.remove_fd(b.as_raw_fd()).unwrap();
+    a.write_all(b"pong").unwrap();
+    assert!(poller.poll_timeout(Duration::from_millis(10)) == PollEvent::Timeout);
+
+    // peer closed
+    poller.add_fd(a.as_raw_fd(), Interest::READABLE, 1).unwrap();
+    drop(b);
+    let event = poller.poll_timeout(Duration::from_millis(100));
+    assert!(event == PollEvent::Io(a.as_raw_fd(), Interest::READABLE));
+}
+
+#[test]
+fn late_fd_test() {
+    let (mut a, b) = UnixStream::pair().unwrap();
+    let (_tx, rx) = channel::<i32>();
+    let poller = Arc::new(Poll::new());
+    poller.add(&rx);
+    a.write_all(b"ping").unwrap();
+
+    // blocked on the channels before the descriptor is added
+    let waiting = poller.clone();
+    let bg = std::thread::spawn(move || waiting.poll_timeout(Duration::from_secs(1)));
+    std::thread::sleep(Duration::from_millis(20));
+    poller.add_fd(b.as_raw_fd(), Interest::READABLE, 0).unwrap();
+    assert!(bg.join().unwrap() == PollEvent::Io(b.as_raw_fd(), Interest::READABLE));
+}
@@ -0,0 +1,14 @@
+use poll_channel::channel;
+
+// own test binary, ids are allocated process wide
+#[test]
+fn test_fixed_id() {
+    let (_tx, rx) = channel::<i32>();
+    assert!(rx.id() == 0);
+
+    let (_tx, rx) = channel::<i32>();
+    assert!(rx.id() == 1);
+
+    let (_tx, rx) = channel::<i32>();
+    assert!(rx.id() == 2);
+}
@@ -0,0 +1,46 @@
+use poll_channel::{oneshot, Poll, PollEvent, TryRecvError};
+use std::time::Duration;
+
+#[test]
+fn oneshot_test() {
+    let (tx, rx) = oneshot();
+    let poller = Poll::new();
+    poller.add(&rx);
+    assert!(rx.try_recv() == Err(TryRecvError::Empty));
+
+    let bg = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(20));
+        tx.send("reply").unwrap();
+    });
+    assert!(poller.poll_event(1.0) == PollEvent::Ready(rx.id()));
+    assert!(rx.recv().unwrap() == "reply");
+    let _ = bg.join();
+
+    // consumed, no disconnect event follows
+    assert!(poller.try_poll() == PollEvent::Timeout);
+    assert!(rx.try_recv() == Err(TryRecvError::Disconnected));
+    assert!(!rx.is_cancelled());
+}
+
+#[test]
+fn cancel_test() {
+    let (tx, rx) = oneshot::<i32>();
+    let poller = Poll::new();
+    poller.add(&rx);
+
+    drop(tx);
+    assert!(poller.try_poll() == PollEvent::Cancelled(rx.id()));
+    assert!(rx.is_cancelled());
+    assert!(rx.recv().is_err());
+
+    // registered after the sender is gone
+    let other = Poll::new();
+    other.add(&rx);
+    assert!(other.try_poll() == PollEvent::Cancelled(rx.id()));
+
+    let (tx, rx) = oneshot();
+    assert!(!tx.is_closed());
+    drop(rx);
+    assert!(tx.is_closed());
+    assert!(tx.send(1).is_err());
+}
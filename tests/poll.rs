@@ -1,4 +1,11 @@
-use poll_channel::{channel, Poll};
+use poll_channel::{
+    bounded, channel, rendezvous, ChannelId, Poll, PollEvent, Pollable, SendTimeoutError,
+    TrySendError, Waker,
+};
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
 
 #[test]
 fn poll_test() -> Result<(), crossbeam::channel::RecvError> {
@@ -42,13 +49,601 @@ fn poll_test() -> Result<(), crossbeam::channel::RecvError> {
 }
 
 #[test]
-fn test_fixed_id() {
-    let (_tx, rx) = channel::<i32>();
-    assert!(rx.id() == 0);
+fn bounded_test() {
+    let (tx, rx) = bounded(1);
+    let poller = Poll::new();
+    poller.add(&rx);
+    assert!(rx.capacity() == Some(1));
 
-    let (_tx, rx) = channel::<i32>();
-    assert!(rx.id() == 1);
+    let bg = std::thread::spawn(move || {
+        tx.send(1).unwrap();
+        // blocks until the first one is received
+        tx.send(2).unwrap();
+    });
+
+    assert!(poller.poll(1.0) == rx.id());
+    std::thread::sleep(Duration::from_millis(50));
+    // second message is not enqueued yet, no notification
+    assert!(rx.len() == 1);
+    assert!(rx.recv().unwrap() == 1);
+    assert!(poller.poll(1.0) == rx.id());
+    assert!(rx.recv().unwrap() == 2);
+    let _ = bg.join();
+}
+
+#[test]
+fn send_timeout_test() {
+    let (tx, rx) = bounded(1);
+    let poller = Poll::new();
+    poller.add(&rx);
+
+    tx.try_send(1).unwrap();
+    assert!(poller.try_poll() == PollEvent::Ready(rx.id()));
+
+    // full, nothing accepted and nothing notified
+    assert!(tx.try_send(2) == Err(TrySendError::Full(2)));
+    let timeout = Duration::from_millis(10);
+    assert!(tx.send_timeout(3, timeout) == Err(SendTimeoutError::Timeout(3)));
+    let deadline = Instant::now() + timeout;
+    assert!(tx.send_deadline(4, deadline) == Err(SendTimeoutError::Timeout(4)));
+    assert!(Instant::now() >= deadline);
+    assert!(rx.recv().unwrap() == 1);
+    assert!(poller.try_poll() == PollEvent::Timeout);
+
+    tx.send(5).unwrap();
+    let bg = std::thread::spawn(move || {
+        // accepted once the message is received
+        tx.send_timeout(6, Duration::from_secs(1)).unwrap();
+        tx.send_deadline(7, Instant::now() + Duration::from_secs(1))
+    });
+    assert!(poller.poll_event(1.0) == PollEvent::Ready(rx.id()));
+    std::thread::sleep(Duration::from_millis(20));
+    assert!(rx.recv().unwrap() == 5);
+    assert!(poller.poll_event(1.0) == PollEvent::Ready(rx.id()));
+    assert!(rx.recv().unwrap() == 6);
+    assert!(rx.recv().unwrap() == 7);
+    assert!(bg.join().unwrap().is_ok());
+
+    // receiver gone
+    let (tx, _) = bounded::<i32>(1);
+    assert!(tx.send_timeout(8, timeout) == Err(SendTimeoutError::Disconnected(8)));
+}
+
+#[test]
+fn writable_test() {
+    let (tx1, rx1) = bounded(1);
+    let (tx2, rx2) = bounded(1);
+    let (id1, id2) = (rx1.id(), rx2.id());
+    let poller = Poll::new();
+    poller.add_sender(&tx1);
+    poller.add_sender(&tx2);
+    // the receiver of a channel in the same poll
+    poller.add(&rx1);
+
+    // room right away
+    assert!(poller.try_poll() == PollEvent::Writable(id1));
+    assert!(poller.try_poll() == PollEvent::Writable(id2));
+    tx1.try_send(1).unwrap();
+    tx2.try_send(2).unwrap();
+    assert!(poller.try_poll() == PollEvent::Ready(rx1.id()));
+    assert!(poller.try_poll() == PollEvent::Timeout);
+
+    // a receive makes room
+    let bg = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(20));
+        (rx2.recv().unwrap(), rx2)
+    });
+    assert!(poller.poll_event(1.0) == PollEvent::Writable(id2));
+    let (n, _rx2) = bg.join().unwrap();
+    assert!(n == 2);
+    tx2.try_send(3).unwrap();
+
+    // at most one until polled
+    assert!(rx1.recv().unwrap() == 1);
+    tx1.try_send(4).unwrap();
+    assert!(rx1.recv().unwrap() == 4);
+    assert!(poller.try_poll() == PollEvent::Writable(id1));
+    assert!(poller.try_poll() == PollEvent::Timeout);
+
+    // receiver gone, sending fails without blocking
+    poller.remove(&rx1);
+    drop(rx1);
+    assert!(poller.try_poll() == PollEvent::Writable(id1));
+    assert!(tx1.try_send(5) == Err(TrySendError::Disconnected(5)));
+
+    poller.remove_sender(&tx2);
+    assert!(poller.try_poll() == PollEvent::Timeout);
+}
+
+#[test]
+fn rendezvous_test() {
+    let (tx, rx) = rendezvous();
+    let poller = Poll::new();
+    poller.add(&rx);
+    assert!(rx.capacity() == Some(0));
+
+    let bg = std::thread::spawn(move || {
+        tx.send(1).unwrap();
+    });
+
+    assert!(rx.recv().unwrap() == 1);
+    let _ = bg.join();
+    // the message was taken before the poll could see it
+    assert!(poller.poll_event(1.0) == PollEvent::Disconnected(rx.id()));
+
+    // driven by the poll, ready once the sender is about to block
+    let (tx, rx) = rendezvous();
+    poller.add(&rx);
+    let bg = std::thread::spawn(move || {
+        tx.send(2).unwrap();
+        tx.send_timeout(3, Duration::from_millis(20))
+    });
+    assert!(poller.poll_event(1.0) == PollEvent::Ready(rx.id()));
+    // it may not be parked yet
+    assert!(rx.recv().unwrap() == 2);
+    // nobody takes the second one
+    assert!(bg.join().unwrap().is_err());
+    assert!(rx.try_recv().is_err());
+    assert!(poller.poll_event(1.0) == PollEvent::Disconnected(rx.id()));
+}
 
+#[test]
+fn rendezvous_writable_test() {
+    let (tx, rx) = rendezvous();
+    let id = rx.id();
+    let poller = Poll::new();
+    poller.add_sender(&tx);
+    let other = tx.clone();
+    let bg = std::thread::spawn(move || other.send(1).unwrap());
+    assert!(rx.recv().unwrap() == 1);
+    let _ = bg.join();
+    // a receive makes no room
+    assert!(poller.try_poll() == PollEvent::Timeout);
+    // sending fails right away
+    drop(rx);
+    assert!(poller.try_poll() == PollEvent::Writable(id));
+}
+
+#[test]
+fn late_register_test() {
+    let (tx1, rx1) = channel();
+    let tx2 = tx1.clone();
+
+    // senders used before the receiver is registered
+    tx1.send(1).unwrap();
+    tx2.send(2).unwrap();
+
+    let poller = Poll::new();
+    poller.add(&rx1);
+    assert!(poller.poll(0.1) == rx1.id());
+    assert!(rx1.recv().unwrap() == 1);
+    // re-armed, a message is left
+    assert!(poller.poll(0.1) == rx1.id());
+    assert!(rx1.recv().unwrap() == 2);
+    assert!(poller.poll(0.01) == -1);
+
+    tx1.send(3).unwrap();
+    tx2.send(4).unwrap();
+    let tx3 = tx2.clone();
+    tx3.send(5).unwrap();
+    for n in 3..6 {
+        assert!(poller.poll(0.1) == rx1.id());
+        assert!(rx1.recv().unwrap() == n);
+    }
+    assert!(poller.poll(0.01) == -1);
+}
+
+#[test]
+fn reregister_test() {
+    let (tx, rx) = channel();
+    let first = Poll::new();
+    let second = Poll::new();
+
+    first.add(&rx);
+    tx.send(1).unwrap();
+    assert!(first.poll(0.1) == rx.id());
+    assert!(rx.recv().unwrap() == 1);
+
+    // move to another poll
+    first.remove(&rx);
+    second.add(&rx);
+    tx.send(2).unwrap();
+    assert!(second.poll(0.1) == rx.id());
+    assert!(first.poll(0.01) == -1);
+    assert!(rx.recv().unwrap() == 2);
+}
+
+#[test]
+fn fan_out_test() {
+    let (tx, rx) = channel();
+    let supervisor = Poll::new();
+    let worker = Poll::new();
+    supervisor.add(&rx);
+    tx.send(1).unwrap();
+    // already ready when added to the second poll
+    worker.add(&rx);
+
+    assert!(supervisor.try_poll() == PollEvent::Ready(rx.id()));
+    assert!(worker.try_poll() == PollEvent::Ready(rx.id()));
+    assert!(worker.try_poll() == PollEvent::Timeout);
+
+    // drained by one, stale for the other
+    tx.send(2).unwrap();
+    assert!(worker.try_poll() == PollEvent::Ready(rx.id()));
+    assert!(rx.recv().unwrap() == 1);
+    assert!(rx.recv().unwrap() == 2);
+    assert!(supervisor.try_poll() == PollEvent::Timeout);
+
+    worker.remove(&rx);
+    tx.send(3).unwrap();
+    assert!(supervisor.try_poll() == PollEvent::Ready(rx.id()));
+    assert!(worker.try_poll() == PollEvent::Timeout);
+
+    drop(tx);
+    worker.add(&rx);
+    assert!(supervisor.try_poll() == PollEvent::Disconnected(rx.id()));
+    assert!(worker.try_poll() == PollEvent::Ready(rx.id()));
+    assert!(worker.try_poll() == PollEvent::Disconnected(rx.id()));
+}
+
+#[test]
+fn remove_test() {
+    let (tx1, rx1) = channel();
+    let (tx2, rx2) = channel();
+    let poller = Poll::new();
+    poller.append(&[&rx1, &rx2]);
+
+    tx1.send(1).unwrap();
+    tx2.send(2).unwrap();
+    tx1.send(3).unwrap();
+
+    // queued ids of rx1 are purged
+    poller.remove(&rx1);
+    assert!(poller.poll(0.1) == rx2.id());
+    assert!(poller.poll(0.01) == -1);
+
+    // senders stop notifying
+    tx1.send(4).unwrap();
+    assert!(poller.poll(0.01) == -1);
+    assert!(rx1.len() == 3);
+
+    tx2.send(5).unwrap();
+    poller.clear();
+    tx2.send(6).unwrap();
+    assert!(poller.poll(0.01) == -1);
+
+    // registered again
+    poller.add(&rx1);
+    for n in [1, 3, 4] {
+        assert!(poller.poll(0.1) == rx1.id());
+        assert!(rx1.recv().unwrap() == n);
+    }
+    assert!(poller.poll(0.01) == -1);
+}
+
+#[test]
+fn disconnect_test() {
+    let (tx, rx) = channel();
+    let poller = Poll::new();
+    poller.add(&rx);
+
+    let tx2 = tx.clone();
+    let bg = std::thread::spawn(move || {
+        tx2.send(1).unwrap();
+    });
+    let _ = bg.join();
+    assert!(poller.poll_event(0.1) == PollEvent::Ready(rx.id()));
+    // one sender left
+    assert!(poller.poll_event(0.01) == PollEvent::Timeout);
+
+    drop(tx);
+    assert!(poller.poll_event(0.1) == PollEvent::Disconnected(rx.id()));
+    assert!(rx.is_disconnected());
+    assert!(rx.recv().unwrap() == 1);
+    assert!(rx.recv().is_err());
+
+    // registered after the senders are gone
+    let other = Poll::new();
+    other.add(&rx);
+    assert!(other.poll_event(0.1) == PollEvent::Disconnected(rx.id()));
+}
+
+#[test]
+fn poll_variants_test() {
+    let (tx, rx) = channel();
+    let poller = Poll::new();
+    poller.add(&rx);
+
+    assert!(poller.try_poll() == PollEvent::Timeout);
+    assert!(poller.poll_timeout(Duration::from_millis(10)) == PollEvent::Timeout);
+    let deadline = std::time::Instant::now() + Duration::from_millis(10);
+    assert!(poller.poll_deadline(deadline) == PollEvent::Timeout);
+    assert!(std::time::Instant::now() >= deadline);
+    // negative timeout doesn't wait
+    assert!(poller.poll_event(-1.0) == PollEvent::Timeout);
+
+    tx.send(1).unwrap();
+    assert!(poller.try_poll() == PollEvent::Ready(rx.id()));
+
+    let bg = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(20));
+        tx.send(2).unwrap();
+    });
+    assert!(poller.poll_blocking() == PollEvent::Ready(rx.id()));
+    assert!(poller.poll_timeout(Duration::MAX) == PollEvent::Disconnected(rx.id()));
+    let _ = bg.join();
+}
+
+#[test]
+fn poll_many_test() {
+    let (tx1, rx1) = channel();
+    let (tx2, rx2) = channel();
+    let (tx3, rx3) = channel();
+    let poller = Poll::new();
+    poller.append(&[&rx1, &rx2, &rx3]);
+    let mut events = Vec::new();
+
+    assert!(poller.poll_many(&mut events, 8, Duration::from_millis(10)) == 0);
+    assert!(events.is_empty());
+
+    for i in 0..3 {
+        tx1.send(i).unwrap();
+    }
+    tx2.send(10).unwrap();
+    tx3.send(20).unwrap();
+    tx1.send(3).unwrap();
+
+    assert!(poller.poll_many(&mut events, 2, Duration::from_millis(10)) == 2);
+    assert!(
+        events
+            == vec![
+                (PollEvent::Ready(rx1.id()), 1),
+                (PollEvent::Ready(rx2.id()), 1)
+            ]
+    );
+
+    drop(tx2);
+    assert!(poller.poll_many(&mut events, 8, Duration::from_millis(10)) == 2);
+    assert!(
+        events
+            == vec![
+                (PollEvent::Ready(rx3.id()), 1),
+                (PollEvent::Disconnected(rx2.id()), 1),
+            ]
+    );
+}
+
+#[test]
+fn coalesce_test() {
+    let (tx, rx) = channel();
+    let poller = Poll::new();
+    poller.add(&rx);
+
+    // one event for many messages
+    for i in 0..1000 {
+        tx.send(i).unwrap();
+    }
+    assert!(poller.try_poll() == PollEvent::Ready(rx.id()));
+    assert!(poller.try_poll() == PollEvent::Timeout);
+
+    // re-armed on every receive leaving messages behind
+    for i in 0..1000 {
+        assert!(rx.try_recv().unwrap() == i);
+        if i < 999 {
+            assert!(poller.try_poll() == PollEvent::Ready(rx.id()));
+        }
+    }
+    assert!(poller.try_poll() == PollEvent::Timeout);
+
+    // drained without polling, the queued event is stale
+    tx.send(1).unwrap();
+    assert!(rx.try_recv().unwrap() == 1);
+    assert!(poller.try_poll() == PollEvent::Timeout);
+
+    // drained then sent again, reported once
+    tx.send(2).unwrap();
+    // it may not be parked yet
+    assert!(rx.recv().unwrap() == 2);
+    tx.send(3).unwrap();
+    assert!(poller.try_poll() == PollEvent::Ready(rx.id()));
+    assert!(poller.try_poll() == PollEvent::Timeout);
+    assert!(rx.try_recv().unwrap() == 3);
+}
+
+struct Source {
+    id: ChannelId,
+    waker: Mutex<Option<Waker>>,
+}
+
+impl Pollable for Source {
+    fn id(&self) -> ChannelId {
+        self.id
+    }
+
+    fn register(&self, waker: Waker) {
+        *self.waker.lock().unwrap() = Some(waker);
+    }
+}
+
+#[test]
+fn custom_source_test() {
+    let source = Source {
+        id: ChannelId::unique(),
+        waker: Mutex::new(None),
+    };
     let (_tx, rx) = channel::<i32>();
-    assert!(rx.id() == 2);
+    assert!(source.id != rx.id());
+
+    let poller = Poll::new();
+    poller.add(&source);
+    let waker = source.waker.lock().unwrap().clone().unwrap();
+    assert!(waker.id() == source.id);
+
+    let bg = std::thread::spawn(move || {
+        waker.notify();
+        waker.disconnect();
+    });
+    let _ = bg.join();
+    assert!(poller.poll_event(1.0) == PollEvent::Ready(source.id));
+    assert!(poller.try_poll() == PollEvent::Disconnected(source.id));
+
+    // removed, the waker goes quiet
+    let waker = source.waker.lock().unwrap().clone().unwrap();
+    poller.remove(&source);
+    assert!(!waker.is_active());
+    waker.notify();
+    assert!(poller.try_poll() == PollEvent::Timeout);
+
+    // a new waker on registering again
+    poller.add(&source);
+    source.waker.lock().unwrap().as_ref().unwrap().notify();
+    assert!(poller.try_poll() == PollEvent::Ready(source.id));
+}
+
+#[test]
+fn work_stealing_test() {
+    let (tx, rx1) = channel();
+    let rx2 = rx1.clone();
+    let rx3 = rx1.clone();
+    assert!(rx2.id() == rx1.id());
+    let polls = [Poll::new(), Poll::new(), Poll::new()];
+    polls[0].add(&rx1);
+    polls[1].add(&rx2);
+    polls[2].add(&rx3);
+    let ready = |polls: &[Poll]| {
+        polls
+            .iter()
+            .filter(|p| p.try_poll() == PollEvent::Ready(rx1.id()))
+            .count()
+    };
+
+    // one message wakes one worker
+    tx.send(1).unwrap();
+    assert!(ready(&polls) == 1);
+    assert!(rx3.recv().unwrap() == 1);
+    assert!(ready(&polls) == 0);
+
+    // as many as there are messages
+    tx.send(2).unwrap();
+    tx.send(3).unwrap();
+    assert!(ready(&polls) == 2);
+    assert!(rx1.try_recv().is_ok() && rx2.try_recv().is_ok());
+    for i in 0..5 {
+        tx.send(i).unwrap();
+    }
+    assert!(ready(&polls) == 3);
+
+    // a dropped worker hands its event over
+    let (tx, rx1) = channel();
+    let rx2 = rx1.clone();
+    let first = Poll::new();
+    let second = Poll::new();
+    first.add(&rx1);
+    second.add(&rx2);
+
+    // either one is woken, rx1 is dropped before polling
+    tx.send(1).unwrap();
+    drop(rx1);
+    assert!(second.try_poll() == PollEvent::Ready(rx2.id()));
+    assert!(rx2.recv().unwrap() == 1);
+
+    // every worker learns about the disconnection
+    let rx3 = rx2.clone();
+    let third = Poll::new();
+    third.add(&rx3);
+    drop(tx);
+    assert!(second.try_poll() == PollEvent::Disconnected(rx2.id()));
+    assert!(third.try_poll() == PollEvent::Disconnected(rx3.id()));
+}
+
+#[test]
+fn worker_pool_test() {
+    let (tx, rx) = channel::<u64>();
+    let workers: Vec<_> = (0..4)
+        .map(|_| {
+            let rx = rx.clone();
+            std::thread::spawn(move || {
+                let poller = Poll::new();
+                poller.add(&rx);
+                let mut sum = 0;
+                loop {
+                    match poller.poll_blocking() {
+                        PollEvent::Ready(_) => {
+                            if let Ok(n) = rx.try_recv() {
+                                sum += n;
+                            }
+                        }
+                        PollEvent::Disconnected(_) => {
+                            while let Ok(n) = rx.try_recv() {
+                                sum += n;
+                            }
+                            return sum;
+                        }
+                        _ => {}
+                    }
+                }
+            })
+        })
+        .collect();
+    drop(rx);
+    for n in 1..=10000 {
+        tx.send(n).unwrap();
+    }
+    drop(tx);
+    let total: u64 = workers.into_iter().map(|w| w.join().unwrap()).sum();
+    assert!(total == 10000 * 10001 / 2);
+}
+
+#[test]
+fn token_test() {
+    let (tx1, rx1) = channel::<i32>();
+    let (tx2, rx2) = channel::<&str>();
+    let (tx3, rx3) = channel::<i32>();
+    let poller = Poll::new();
+    poller.add_with_token(&rx1, 0);
+    poller.add_with_token(&rx2, 1);
+    // no token
+    poller.add(&rx3);
+
+    let mut got = 0;
+    let mut handlers: Vec<Box<dyn FnMut()>> = vec![
+        Box::new(|| got += rx1.recv().unwrap()),
+        Box::new(|| assert!(rx2.recv().unwrap() == "two")),
+    ];
+    tx2.send("two").unwrap();
+    tx1.send(1).unwrap();
+    tx3.send(3).unwrap();
+
+    let timeout = Duration::from_millis(10);
+    assert!(poller.poll_token(timeout) == Some((Some(1), PollEvent::Ready(rx2.id()))));
+    handlers[1]();
+    let (token, event) = poller.poll_token(timeout).unwrap();
+    assert!(token == Some(0) && event == PollEvent::Ready(rx1.id()));
+    handlers[token.unwrap()]();
+    assert!(poller.poll_token(timeout) == Some((None, PollEvent::Ready(rx3.id()))));
+    assert!(poller.poll_token(timeout).is_none());
+
+    // registered again with another token
+    poller.add_with_token(&rx3, 7);
+    drop(tx3);
+    assert!(poller.poll_token(timeout) == Some((Some(7), PollEvent::Ready(rx3.id()))));
+    assert!(poller.poll_token(timeout) == Some((Some(7), PollEvent::Disconnected(rx3.id()))));
+    drop(handlers);
+    assert!(got == 1);
+
+    // a sender
+    let (tx4, rx4) = bounded::<i32>(1);
+    poller.add_sender_with_token(&tx4, 8);
+    assert!(poller.poll_token(timeout) == Some((Some(8), PollEvent::Writable(rx4.id()))));
+}
+
+#[test]
+fn no_token_test() {
+    let (tx, rx) = channel::<i32>();
+    let poller = Poll::new();
+    poller.add(&rx);
+    drop(tx);
+    let timeout = Duration::from_millis(10);
+    assert!(poller.poll_token(timeout) == Some((None, PollEvent::Disconnected(rx.id()))));
+    assert!(poller.poll_token(timeout).is_none());
 }
@@ -0,0 +1,77 @@
+use poll_channel::{bounded, channel, select};
+use std::time::{Duration, Instant};
+
+#[test]
+fn select_recv_test() {
+    let (tx1, rx1) = channel::<i32>();
+    let (tx2, rx2) = channel::<&str>();
+
+    let bg = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(20));
+        tx2.send("hello").unwrap();
+    });
+
+    // blocks until the thread sends
+    let s = select! {
+        recv(rx1) -> msg => panic!("unexpected {:?}", msg),
+        recv(rx2) -> msg => msg.unwrap(),
+    };
+    assert!(s == "hello");
+    let _ = bg.join();
+
+    // disconnected
+    let closed = select! {
+        recv(rx2) -> msg => msg.is_err(),
+        default(Duration::from_millis(100)) => false,
+    };
+    assert!(closed);
+
+    tx1.send(1).unwrap();
+    let mut got = 0;
+    for _ in 0..3 {
+        select! {
+            recv(rx1) -> msg => {
+                got += msg.unwrap();
+                continue;
+            }
+            default => break,
+        }
+    }
+    assert!(got == 1);
+}
+
+#[test]
+fn select_send_test() {
+    let (tx, rx) = bounded(1);
+
+    let sent = select! {
+        send(tx, 1) -> res => res.is_ok(),
+        default => false,
+    };
+    assert!(sent);
+
+    // full
+    let start = Instant::now();
+    let sent = select! {
+        send(tx, 2) -> res => res.is_ok(),
+        default(Duration::from_millis(20)) => false,
+    };
+    assert!(!sent);
+    assert!(start.elapsed() >= Duration::from_millis(20));
+
+    // room made by another thread
+    let bg = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(rx.recv().unwrap() == 1);
+        assert!(rx.recv().unwrap() == 3);
+    });
+    select! {
+        send(tx, 3) -> res => res.unwrap(),
+    }
+    let _ = bg.join();
+
+    let failed = select! {
+        send(tx, 4) -> res => res.unwrap_err().into_inner() == 4,
+    };
+    assert!(failed);
+}
@@ -0,0 +1,48 @@
+use poll_channel::{channel, Selector};
+use std::time::Duration;
+
+#[test]
+fn selector_test() {
+    let (tx1, rx1) = channel();
+    let (tx2, rx2) = channel();
+    let mut numbers = Vec::new();
+    let mut strings = Vec::new();
+    let mut closed = 0;
+
+    {
+        let mut selector = Selector::new();
+        selector
+            .recv(&rx1, |n: Result<i32, _>| match n {
+                Ok(n) => numbers.push(n),
+                Err(_) => closed += 1,
+            })
+            .recv(&rx2, |s: Result<String, _>| strings.push(s.unwrap()));
+        assert!(selector.len() == 2);
+
+        let bg = std::thread::spawn(move || {
+            for i in 0..3 {
+                tx1.send(i).unwrap();
+            }
+        });
+        tx2.send("hello".to_string()).unwrap();
+
+        let mut ids = Vec::new();
+        while let Some(id) = selector.select(Duration::from_millis(100)) {
+            ids.push(id);
+        }
+        let _ = bg.join();
+        assert!(ids.iter().filter(|id| **id == rx1.id()).count() == 4);
+        assert!(ids.iter().filter(|id| **id == rx2.id()).count() == 1);
+        // disconnected receiver is removed
+        assert!(selector.len() == 1);
+
+        selector.remove(&rx2);
+        tx2.send("world".to_string()).unwrap();
+        assert!(selector.select(Duration::from_millis(10)).is_none());
+    }
+
+    assert!(numbers == [0, 1, 2]);
+    assert!(strings == ["hello"]);
+    assert!(closed == 1);
+    assert!(rx2.try_recv().unwrap() == "world");
+}
@@ -0,0 +1,71 @@
+#![cfg(unix)]
+
+use poll_channel::{signals, Poll, PollEvent, SIGHUP, SIGUSR1, SIGUSR2};
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+#[test]
+fn signal_test() {
+    let rx = signals(&[SIGUSR1, SIGUSR2]).unwrap();
+    let other = signals(&[SIGUSR2]).unwrap();
+    let poller = Poll::new();
+    poller.append(&[&rx, &other]);
+
+    // SAFETY: handlers are installed
+    unsafe { libc::raise(SIGUSR1) };
+    assert!(poller.poll_timeout(Duration::from_secs(1)) == PollEvent::Ready(rx.id()));
+    assert!(rx.recv().unwrap() == SIGUSR1);
+    assert!(other.is_empty());
+
+    // every subscriber gets it
+    unsafe { libc::raise(SIGUSR2) };
+    let mut ready = Vec::new();
+    for _ in 0..2 {
+        match poller.poll_timeout(Duration::from_secs(1)) {
+            PollEvent::Ready(id) => ready.push(id),
+            event => panic!("unexpected {:?}", event),
+        }
+    }
+    ready.sort();
+    assert!(ready == [rx.id(), other.id()]);
+    assert!(rx.recv().unwrap() == SIGUSR2);
+    assert!(other.recv().unwrap() == SIGUSR2);
+}
+
+static HANDLED: AtomicUsize = AtomicUsize::new(0);
+
+extern "C" fn count(_: libc::c_int) {
+    HANDLED.fetch_add(1, Ordering::SeqCst);
+}
+
+fn handled(n: usize) -> bool {
+    let deadline = Instant::now() + Duration::from_secs(1);
+    while HANDLED.load(Ordering::SeqCst) < n && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    HANDLED.load(Ordering::SeqCst) == n
+}
+
+#[test]
+fn restore_test() {
+    // SAFETY: count only touches an atomic
+    unsafe {
+        libc::signal(
+            SIGHUP,
+            count as extern "C" fn(libc::c_int) as libc::sighandler_t,
+        )
+    };
+    let rx = signals(&[SIGHUP]).unwrap();
+    unsafe { libc::raise(SIGHUP) };
+    assert!(rx.recv_timeout(Duration::from_secs(1)).unwrap() == SIGHUP);
+    assert!(HANDLED.load(Ordering::SeqCst) == 0);
+
+    // the previous handler gets it back, this signal included
+    drop(rx);
+    unsafe { libc::raise(SIGHUP) };
+    assert!(handled(1));
+    unsafe { libc::raise(SIGHUP) };
+    assert!(handled(2));
+}
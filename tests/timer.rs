@@ -0,0 +1,58 @@
+use poll_channel::{after, at, tick, Poll, PollEvent};
+use std::time::{Duration, Instant};
+
+#[test]
+fn after_test() {
+    let start = Instant::now();
+    let timer = after(Duration::from_millis(20));
+    let poller = Poll::new();
+    poller.add(&timer);
+
+    assert!(poller.poll_blocking() == PollEvent::Ready(timer.id()));
+    let fired = timer.recv().unwrap();
+    assert!(fired >= start + Duration::from_millis(20));
+    // fired once
+    assert!(poller.poll_blocking() == PollEvent::Disconnected(timer.id()));
+    assert!(timer.recv().is_err());
+
+    // deadline passed already
+    let timer = at(start);
+    assert!(timer.recv_timeout(Duration::from_millis(100)).is_ok());
+}
+
+#[test]
+fn tick_test() {
+    let start = Instant::now();
+    let short = tick(Duration::from_millis(10));
+    let long = after(Duration::from_millis(55));
+    let poller = Poll::new();
+    poller.append(&[&short, &long]);
+
+    let mut ticks = Vec::new();
+    loop {
+        match poller.poll_timeout(Duration::from_secs(1)) {
+            PollEvent::Ready(id) if id == short.id() => ticks.push(short.recv().unwrap()),
+            PollEvent::Ready(id) if id == long.id() => break,
+            event => panic!("unexpected {:?}", event),
+        }
+    }
+    assert!((1..=5).contains(&ticks.len()));
+    for (i, t) in ticks.iter().enumerate() {
+        assert!(*t >= start + Duration::from_millis(10) * (i as u32 + 1));
+    }
+
+    // ticks are dropped while not received
+    std::thread::sleep(Duration::from_millis(50));
+    assert!(short.len() == 1);
+}
+
+#[test]
+fn zero_tick_test() {
+    // clamped, other timers keep working
+    let fast = tick(Duration::ZERO);
+    let timer = after(Duration::from_millis(1));
+    assert!(timer.recv_timeout(Duration::from_secs(1)).is_ok());
+    let first = fast.recv().unwrap();
+    let second = fast.recv().unwrap();
+    assert!(second > first);
+}
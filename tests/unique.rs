@@ -0,0 +1,31 @@
+use poll_channel::{channel, ChannelId};
+use std::{collections::HashSet, sync::Barrier};
+
+#[test]
+fn unique_id_test() {
+    const THREADS: usize = 8;
+    const CHANNELS: usize = 10000;
+    let barrier = Barrier::new(THREADS);
+
+    let ids: Vec<Vec<ChannelId>> = std::thread::scope(|s| {
+        let workers: Vec<_> = (0..THREADS)
+            .map(|_| {
+                s.spawn(|| {
+                    barrier.wait();
+                    (0..CHANNELS)
+                        .map(|_| channel::<()>().1.id())
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        workers.into_iter().map(|w| w.join().unwrap()).collect()
+    });
+
+    // increasing in every thread, unique across them
+    for ids in &ids {
+        assert!(ids.windows(2).all(|w| w[0] < w[1]));
+    }
+    let all: HashSet<ChannelId> = ids.into_iter().flatten().collect();
+    assert!(all.len() == THREADS * CHANNELS);
+    assert!(all.iter().all(|id| id.as_i64() >= 0 && *id != -1));
+}
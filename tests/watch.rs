@@ -0,0 +1,57 @@
+use poll_channel::{watch, Poll, PollEvent, RecvTimeoutError};
+use std::time::Duration;
+
+#[test]
+fn watch_test() {
+    let (tx, rx) = watch::channel("initial");
+    let poller = Poll::new();
+    poller.add(&rx);
+
+    // initial value is already seen
+    assert!(poller.try_poll() == PollEvent::Timeout);
+    assert!(rx.has_changed() == Ok(false));
+    assert!(*rx.borrow() == "initial");
+
+    // many updates, one wakeup
+    tx.send("a").unwrap();
+    tx.send("b").unwrap();
+    assert!(tx.send_replace("c").unwrap() == "b");
+    assert!(poller.try_poll() == PollEvent::Ready(rx.id()));
+    assert!(poller.try_poll() == PollEvent::Timeout);
+
+    // borrow doesn't mark seen
+    assert!(*rx.borrow() == "c");
+    assert!(rx.has_changed() == Ok(true));
+    rx.changed().unwrap();
+    assert!(rx.has_changed() == Ok(false));
+    assert!(rx.changed_timeout(Duration::from_millis(10)) == Err(RecvTimeoutError::Timeout));
+
+    // seen before polling, nothing reported
+    tx.send("d").unwrap();
+    assert!(*rx.borrow_and_update() == "d");
+    assert!(poller.try_poll() == PollEvent::Timeout);
+
+    let tx2 = tx.clone();
+    let bg = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(20));
+        tx2.send("e").unwrap();
+    });
+    assert!(poller.poll_event(1.0) == PollEvent::Ready(rx.id()));
+    rx.changed().unwrap();
+    assert!(*tx.borrow() == "e");
+    let _ = bg.join();
+
+    drop(tx);
+    assert!(poller.poll_event(0.1) == PollEvent::Disconnected(rx.id()));
+    assert!(rx.changed().is_err());
+    assert!(*rx.borrow() == "e");
+}
+
+#[test]
+fn watch_closed_test() {
+    let (tx, rx) = watch::channel(0);
+    assert!(!tx.is_closed());
+    drop(rx);
+    assert!(tx.is_closed());
+    assert!(tx.send(1).is_err());
+}